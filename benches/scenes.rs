@@ -0,0 +1,28 @@
+use std::time::Instant;
+
+use raytracing_in_one_weekend::scene;
+
+/// Times a small, seeded render of every scene preset. Run with
+/// `cargo bench`.
+fn main() {
+    for name in scene::NAMES {
+        let scene = scene::by_name(name).unwrap();
+        let cam = scene
+            .camera
+            .image_width(160)
+            .samples_per_pixel(16)
+            .max_depth(16)
+            .seed(1)
+            .build();
+
+        let start = Instant::now();
+        let image = cam.render(&scene.world);
+        let elapsed = start.elapsed();
+        let samples = image.width() as f64 * image.height() as f64 * 16.0;
+        println!(
+            "{name:<18} {:>8.1} ms {:>8.2} M samples/s",
+            elapsed.as_secs_f64() * 1e3,
+            samples / elapsed.as_secs_f64() / 1e6
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    ops::{Add, Mul, Sub},
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, ImageResult,
+};
+
+use crate::{
+    camera::{Camera, CameraBuilder},
+    hittable::Hittable,
+    output,
+    projection::Projection,
+    transform::Transform,
+    vec3::Vec3,
+};
+
+/// Values a `Track` can blend between.
+pub trait Animatable:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self>
+{
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>> Animatable for T {}
+
+/// How a track gets from one keyframe to the next.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Interpolation<T> {
+    #[default]
+    Linear,
+    /// Cubic Bézier curve with the two given control points.
+    Bezier(T, T),
+    /// Smooth curve through the keyframes, whose slope at each one is set
+    /// by its neighbours.
+    CatmullRom,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe<T> {
+    /// Time in seconds.
+    pub time: f64,
+    pub value: T,
+    /// Curve from this keyframe to the next.
+    pub interpolation: Interpolation<T>,
+}
+
+/// A value changing over time, set at keyframes. Before the first keyframe
+/// and after the last the value holds still.
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T> Default for Track<T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<T: Animatable> Track<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a keyframe at `time` seconds, replacing any already there.
+    pub fn key(mut self, time: f64, value: T, interpolation: Interpolation<T>) -> Self {
+        let key = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+        match self.keys.binary_search_by(|k| k.time.total_cmp(&time)) {
+            Ok(i) => self.keys[i] = key,
+            Err(i) => self.keys.insert(i, key),
+        }
+        self
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    /// Value at `time` seconds, or `None` for a track without keyframes. A
+    /// NaN time gets the first keyframe's value.
+    pub fn at(&self, time: f64) -> Option<T> {
+        let (first, last) = (self.keys.first()?, self.keys.last()?);
+        if time.is_nan() || time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+
+        let i = self.keys.partition_point(|k| k.time <= time) - 1;
+        let (from, to) = (&self.keys[i], &self.keys[i + 1]);
+        let duration = to.time - from.time;
+        let u = (time - from.time) / duration;
+        Some(match from.interpolation {
+            Interpolation::Linear => from.value + (to.value - from.value) * u,
+            Interpolation::Bezier(c1, c2) => {
+                let v = 1.0 - u;
+                from.value * (v * v * v)
+                    + c1 * (3.0 * v * v * u)
+                    + c2 * (3.0 * v * u * u)
+                    + to.value * (u * u * u)
+            }
+            Interpolation::CatmullRom => {
+                // Cubic Hermite with the slopes of the chords around each key.
+                let (m0, m1) = (self.slope(i), self.slope(i + 1));
+                let (u2, u3) = (u * u, u * u * u);
+                from.value * (2.0 * u3 - 3.0 * u2 + 1.0)
+                    + m0 * ((u3 - 2.0 * u2 + u) * duration)
+                    + to.value * (3.0 * u2 - 2.0 * u3)
+                    + m1 * ((u3 - u2) * duration)
+            }
+        })
+    }
+
+    /// Rate of change through keyframe `i`, per second.
+    fn slope(&self, i: usize) -> T {
+        let before = &self.keys[i.saturating_sub(1)];
+        let after = &self.keys[(i + 1).min(self.keys.len() - 1)];
+        (after.value - before.value) * (1.0 / (after.time - before.time))
+    }
+}
+
+/// Keyframed camera placement. Empty tracks leave the builder's setting
+/// alone.
+#[derive(Clone, Debug, Default)]
+pub struct CameraTracks {
+    pub center: Track<Vec3>,
+    pub look_at: Track<Vec3>,
+    /// Vertical field of view in degrees, which makes the projection
+    /// `Perspective`.
+    pub vfov: Track<f64>,
+}
+
+impl CameraTracks {
+    pub fn apply(&self, mut camera: CameraBuilder, time: f64) -> CameraBuilder {
+        if let Some(center) = self.center.at(time) {
+            camera = camera.center(center);
+        }
+        if let Some(look_at) = self.look_at.at(time) {
+            camera = camera.look_at(look_at);
+        }
+        if let Some(vfov) = self.vfov.at(time) {
+            camera = camera.projection(Projection::Perspective { vfov });
+        }
+        camera
+    }
+}
+
+/// Keyframed `Transform` of an object. Empty tracks keep the identity.
+#[derive(Clone, Debug, Default)]
+pub struct TransformTracks {
+    pub translation: Track<Vec3>,
+    pub rotation: Track<Vec3>,
+    pub scale: Track<f64>,
+}
+
+impl TransformTracks {
+    pub fn at(&self, time: f64) -> Transform {
+        let identity = Transform::default();
+        Transform {
+            translation: self.translation.at(time).unwrap_or(identity.translation),
+            rotation: self.rotation.at(time).unwrap_or(identity.rotation),
+            scale: self.scale.at(time).unwrap_or(identity.scale),
+        }
+    }
+}
+
+/// Renders a sequence of frames. The scene is rebuilt for every frame from
+/// its time in seconds, so objects can be placed with `TransformTracks`.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    pub frame_count: u32,
+    /// Frames per second. Must be positive.
+    pub frame_rate: f64,
+    /// Seed of the first frame. Frame `n` renders with `seed + n`, so its
+    /// noise doesn't stand still, and a checkpoint left by another frame is
+    /// ignored.
+    pub seed: u64,
+    pub camera: CameraTracks,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            frame_count: 48,
+            frame_rate: 24.0,
+            seed: 0,
+            camera: CameraTracks::default(),
+        }
+    }
+}
+
+impl Animation {
+    /// Time of `frame` in seconds.
+    pub fn time(&self, frame: u32) -> f64 {
+        frame as f64 / self.frame_rate()
+    }
+
+    fn frame_rate(&self) -> f64 {
+        assert!(
+            self.frame_rate > 0.0 && self.frame_rate.is_finite(),
+            "animation frame rate must be positive, got {}",
+            self.frame_rate
+        );
+        self.frame_rate
+    }
+
+    /// The camera for `frame`: `camera` moved along the tracks, with the
+    /// frame's seed.
+    pub fn camera(&self, camera: &CameraBuilder, frame: u32) -> Camera {
+        self.camera
+            .apply(camera.clone(), self.time(frame))
+            .seed(self.seed.wrapping_add(frame as u64))
+            .build()
+    }
+
+    /// File `frame` is saved to in `directory`.
+    pub fn frame_path(directory: impl AsRef<Path>, frame: u32) -> PathBuf {
+        directory.as_ref().join(format!("frame_{frame:04}.png"))
+    }
+
+    /// First frame not yet saved in `directory`, where an interrupted
+    /// sequence picks up again.
+    pub fn next_frame(&self, directory: impl AsRef<Path>) -> u32 {
+        (0..self.frame_count)
+            .find(|&frame| !Self::frame_path(&directory, frame).exists())
+            .unwrap_or(self.frame_count)
+    }
+
+    /// Renders frames `first_frame..frame_count` of `scene` and saves them as
+    /// numbered PNGs in `directory`, which is created if needed. Each frame
+    /// is written under another name first and then renamed, so a frame cut
+    /// off mid-write never counts as saved.
+    pub fn render_sequence<W: Hittable>(
+        &self,
+        camera: &CameraBuilder,
+        scene: impl Fn(f64) -> W,
+        directory: impl AsRef<Path>,
+        first_frame: u32,
+    ) -> ImageResult<()> {
+        fs::create_dir_all(&directory)?;
+        for frame in first_frame..self.frame_count {
+            let cam = self.camera(camera, frame);
+            let image = cam.render(&scene(self.time(frame)));
+            let path = Self::frame_path(&directory, frame);
+            let partial = path.with_extension("partial.png");
+            output::save(&image, &cam.display, cam.alpha_mode, &partial)?;
+            fs::rename(partial, path)?;
+        }
+        Ok(())
+    }
+
+    /// Gathers the PNGs in `directory` into an animated GIF that loops
+    /// forever.
+    pub fn save_gif(&self, directory: impl AsRef<Path>, path: impl AsRef<Path>) -> ImageResult<()> {
+        // In thousandths of a frame per second, at least one.
+        let millihertz = ((self.frame_rate() * 1000.0).round() as u32).max(1);
+        let delay = Delay::from_numer_denom_ms(1_000_000, millihertz);
+        let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+        encoder.set_repeat(Repeat::Infinite)?;
+        for frame in 0..self.frame_count {
+            let image = image::open(Self::frame_path(&directory, frame))?.into_rgba8();
+            encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use image::{ImageResult, Rgb, Rgb32FImage};
+
+use crate::{
+    color::{self, Color},
+    hittable::HitRecord,
+    ray::{Point3, Ray},
+    vec3::Vec3,
+};
+
+/// Which arbitrary output variables `Camera::render_with_aovs` fills in.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AovSelection {
+    pub depth: bool,
+    pub normal: bool,
+    pub albedo: bool,
+    pub position: bool,
+    pub object_id: bool,
+    pub sample_count: bool,
+}
+
+impl AovSelection {
+    pub fn all() -> Self {
+        Self {
+            depth: true,
+            normal: true,
+            albedo: true,
+            position: true,
+            object_id: true,
+            sample_count: true,
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.depth
+            || self.normal
+            || self.albedo
+            || self.position
+            || self.object_id
+            || self.sample_count
+    }
+}
+
+/// Per-pixel feature buffers taken from the first hit of the camera rays.
+///
+/// Normal and albedo are averaged over all samples of a pixel so they are
+/// anti-aliased like the beauty pass. Depth, position and object ID come
+/// from the pixel's first sample, since averaging them across an edge gives
+/// values that belong to neither object. Pixels that see the background get
+/// infinite depth, zero normal/albedo/position and object ID 0; objects are
+/// numbered from 1.
+pub struct Aovs {
+    pub depth: Option<Rgb32FImage>,
+    pub normal: Option<Rgb32FImage>,
+    pub albedo: Option<Rgb32FImage>,
+    pub position: Option<Rgb32FImage>,
+    pub object_id: Option<Rgb32FImage>,
+    pub sample_count: Option<Rgb32FImage>,
+}
+
+/// Running sums for the pixel being rendered.
+#[derive(Default)]
+pub(crate) struct AovPixel {
+    pub(crate) normal: Color,
+    pub(crate) albedo: Color,
+    pub(crate) first: Option<(f64, Point3, f64)>,
+}
+
+impl AovPixel {
+    /// Adds one camera sample. `forward` is the camera's viewing direction,
+    /// used to turn hit distances into camera-space depth.
+    pub(crate) fn add(&mut self, ray: &Ray, forward: &Vec3, hit: Option<&HitRecord>) {
+        let first = match hit {
+            Some(rec) => {
+                self.normal += rec.normal;
+                self.albedo += rec.mat.albedo(rec);
+                let depth = (rec.p - ray.origin).dot(forward);
+                (depth, rec.p, rec.object_id as f64 + 1.0)
+            }
+            None => (f64::INFINITY, Point3::default(), 0.0),
+        };
+        self.first.get_or_insert(first);
+    }
+}
+
+impl Aovs {
+    pub(crate) fn new(selection: &AovSelection, width: u32, height: u32) -> Self {
+        let buffer = |enabled: bool| enabled.then(|| Rgb32FImage::new(width, height));
+        Self {
+            depth: buffer(selection.depth),
+            normal: buffer(selection.normal),
+            albedo: buffer(selection.albedo),
+            position: buffer(selection.position),
+            object_id: buffer(selection.object_id),
+            sample_count: buffer(selection.sample_count),
+        }
+    }
+
+    pub(crate) fn write_pixel(&mut self, x: u32, y: u32, pixel: &AovPixel, samples: u32) {
+        let scale = 1.0 / samples.max(1) as f64;
+        let (depth, position, id) = pixel
+            .first
+            .unwrap_or((f64::INFINITY, Point3::default(), 0.0));
+        let splat = |v: f64| Color::new(v, v, v);
+
+        let put = |buffer: &mut Option<Rgb32FImage>, value: Color| {
+            if let Some(buffer) = buffer {
+                buffer.put_pixel(x, y, Rgb(color::color_rgb32f(value)));
+            }
+        };
+        put(&mut self.depth, splat(depth));
+        put(&mut self.normal, pixel.normal * scale);
+        put(&mut self.albedo, pixel.albedo * scale);
+        put(&mut self.position, position);
+        put(&mut self.object_id, splat(id));
+        put(&mut self.sample_count, splat(samples as f64));
+    }
+
+    /// Named buffers that were rendered.
+    pub fn layers(&self) -> impl Iterator<Item = (&'static str, &Rgb32FImage)> {
+        [
+            ("depth", &self.depth),
+            ("normal", &self.normal),
+            ("albedo", &self.albedo),
+            ("position", &self.position),
+            ("object_id", &self.object_id),
+            ("sample_count", &self.sample_count),
+        ]
+        .into_iter()
+        .filter_map(|(name, buffer)| buffer.as_ref().map(|b| (name, b)))
+    }
+
+    /// Saves every rendered buffer next to the beauty image at `path`, as
+    /// `<stem>.<aov>.exr`.
+    pub fn save(&self, path: impl AsRef<Path>) -> ImageResult<()> {
+        let path = path.as_ref();
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("render");
+        for (name, buffer) in self.layers() {
+            buffer.save(path.with_file_name(format!("{stem}.{name}.exr")))?;
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,35 @@
 use crate::{
+    aov::{AovPixel, AovSelection, Aovs},
+    checkpoint,
     color::{self, Color},
-    hittable::Hittable,
+    denoise::Denoiser,
+    environment::{Environment, Gradient},
+    film::Film,
+    filter::Filter,
+    hittable::{HitRecord, Hittable},
     interval::Interval,
+    lens::{LensSystem, RealisticLens},
+    material::Matte,
+    output::{self, AlphaMode},
+    projection::{Projection, Stereo, StereoLayout},
     ray::{Point3, Ray},
-    util::random_f64,
+    sampler::{Sampler, SamplerKind},
+    spectrum,
+    tonemap::{DisplayTransform, ToneMapper, TransferFunction},
     vec3::Vec3,
 };
 
-use image::{ImageBuffer, Rgb, RgbImage};
+use std::{
+    ops::ControlFlow,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use image::Rgba32FImage;
 use indicatif::ProgressBar;
 
 pub struct Camera {
@@ -15,102 +37,639 @@ pub struct Camera {
     pub image_width: u32,
     pub image_height: u32,
     pub center: Point3,
+    pub projection: Projection,
     pub max_depth: i32,
-    pub sky_color: Color,
+    /// Light arriving from outside the scene.
+    pub environment: Box<dyn Environment>,
+    pub spectral: bool,
+    /// Display transform for 8-bit output, see `output::to_rgb8`.
+    pub display: DisplayTransform,
+    /// Alpha convention of the snapshots, see `output::save`.
+    pub alpha_mode: AlphaMode,
+    pub aovs: AovSelection,
+    pub denoiser: Option<Denoiser>,
 
     samples_per_pixel: i32,
-    pixel00_loc: Point3,
-    pixel_delta_u: Vec3,
-    pixel_delta_v: Vec3,
-    pixel_samples_scale: f64,
+    adaptive: Option<AdaptiveSampling>,
+    sampler: SamplerKind,
+    seed: u64,
+    filter: Filter,
+    samples_per_pass: Option<i32>,
+    snapshot: Option<PathBuf>,
+    checkpoint: Option<PathBuf>,
+    checkpoint_interval: Duration,
+    time_budget: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    stereo: Option<Stereo>,
+    lens: Option<LensSystem>,
+    /// Camera basis: image right, image up and viewing direction.
+    right: Vec3,
+    up: Vec3,
+    forward: Vec3,
+}
+
+/// Per-pixel sample counts driven by noise. Every pixel gets `min_samples`,
+/// then keeps sampling until the standard error of its mean (measured on
+/// Reinhard-compressed luminance) drops below `noise_threshold`, or it
+/// reaches `max_samples`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveSampling {
+    pub min_samples: i32,
+    pub max_samples: i32,
+    pub noise_threshold: f64,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        Self {
+            min_samples: 16,
+            max_samples: 1024,
+            noise_threshold: 0.005,
+        }
+    }
+}
+
+/// Shared flag for stopping a render from another thread. Clones refer to
+/// the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Welford's online mean and variance.
+#[derive(Default)]
+pub(crate) struct RunningVariance {
+    pub(crate) count: f64,
+    pub(crate) mean: f64,
+    pub(crate) m2: f64,
+}
+
+impl RunningVariance {
+    fn add(&mut self, value: f64) {
+        self.count += 1.0;
+        let delta = value - self.mean;
+        self.mean += delta / self.count;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    fn standard_error(&self) -> f64 {
+        if self.count < 2.0 {
+            return f64::INFINITY;
+        }
+        let variance = self.m2 / (self.count - 1.0);
+        (variance / self.count).sqrt()
+    }
+}
+
+/// Accumulation state of one pixel across passes.
+#[derive(Default)]
+pub(crate) struct PixelState {
+    pub(crate) samples: i32,
+    pub(crate) aov: AovPixel,
+    pub(crate) variance: RunningVariance,
+    /// Reached its sample budget or converged.
+    pub(crate) done: bool,
+}
+
+/// Everything a render has accumulated so far, see `checkpoint`.
+pub(crate) struct RenderState {
+    /// Passes done so far.
+    pub(crate) pass: u32,
+    pub(crate) film: Film,
+    pub(crate) pixels: Vec<PixelState>,
+}
+
+/// What a camera sample sends into the scene.
+enum CameraRay {
+    /// A ray whose radiance counts with the given weight.
+    Ray(Ray, f64),
+    /// Nothing: the position is outside what the projection covers, and
+    /// stays transparent.
+    Outside,
+    /// Nothing: the lens stopped the light, which leaves it black.
+    Blocked,
+}
+
+/// A finished pass of `Camera::render_progressive`.
+pub struct Pass<'a> {
+    /// Passes done so far, starting at 1.
+    pub index: u32,
+    /// Samples per pixel so far. Pixels that converged under adaptive
+    /// sampling may have fewer.
+    pub samples_per_pixel: i32,
+    /// Linear radiance accumulated so far.
+    pub image: &'a Rgba32FImage,
 }
 
 impl Camera {
-    pub fn render(&self, world: &dyn Hittable) -> RgbImage {
-        let mut buffer: RgbImage = ImageBuffer::new(self.image_width, self.image_height);
+    /// Renders the linear radiance of `world`. Use the `output` module to
+    /// turn it into a displayable image or to write it as HDR.
+    pub fn render(&self, world: &dyn Hittable) -> Rgba32FImage {
+        self.render_with_aovs(world).0
+    }
 
-        let pixel_count = self.image_width * self.image_height;
-        let pb = ProgressBar::new(pixel_count as u64);
+    /// Like `render`, but also returns the feature buffers selected with
+    /// `CameraBuilder::aovs`.
+    pub fn render_with_aovs(&self, world: &dyn Hittable) -> (Rgba32FImage, Aovs) {
+        self.render_progressive(world, |_| ControlFlow::Continue(()))
+    }
 
-        for (x, y, pixel) in buffer.enumerate_pixels_mut() {
-            let mut color = Color::new(0.0, 0.0, 0.0);
-            for _ in 0..self.samples_per_pixel {
-                let r = self.get_ray(x as i32, y as i32);
-                color += self.ray_color(&r, self.max_depth, world);
+    /// Renders in passes of `CameraBuilder::samples_per_pass` samples over
+    /// the whole image (one sample under a time budget, cancellation token or
+    /// checkpoint, all of them otherwise). After every pass the snapshot (if
+    /// any) is written and `on_pass` is called with the image so far;
+    /// returning `ControlFlow::Break` stops the render early with that image.
+    ///
+    /// A render that runs out of its time budget or is cancelled stops
+    /// mid-pass; that partial pass is reported like any other. Every pixel is
+    /// normalized by the samples it actually got, so the image stays
+    /// unbiased, just noisier (and black where nothing was sampled yet).
+    pub fn render_progressive(
+        &self,
+        world: &dyn Hittable,
+        mut on_pass: impl FnMut(&Pass) -> ControlFlow<()>,
+    ) -> (Rgba32FImage, Aovs) {
+        // The denoiser is guided by the albedo and normal buffers, so those
+        // are rendered whenever it is enabled.
+        let mut selection = self.aovs;
+        if self.denoiser.is_some() {
+            selection.albedo = true;
+            selection.normal = true;
+        }
+        let mut aovs = Aovs::new(&selection, self.image_width, self.image_height);
+
+        let (min_samples, max_samples) = match &self.adaptive {
+            Some(adaptive) => (adaptive.min_samples, adaptive.max_samples),
+            None => (self.samples_per_pixel, self.samples_per_pixel),
+        };
+        let mut sampler = self.sampler.build(self.seed, min_samples as u32);
+
+        // A render that may stop early goes one sample per pixel at a time,
+        // so stopping leaves a noisy image rather than unsampled scanlines.
+        // The same goes for a checkpointed render, which may be killed.
+        let may_stop =
+            self.time_budget.is_some() || self.cancellation.is_some() || self.checkpoint.is_some();
+        let samples_per_pass = match self.samples_per_pass {
+            Some(samples_per_pass) => samples_per_pass,
+            None if may_stop => 1,
+            None => max_samples,
+        }
+        .max(1);
+        let passes = (max_samples.max(1) as u32).div_ceil(samples_per_pass as u32);
+
+        // Described once, since an environment map makes that slow.
+        let checkpoint = self
+            .checkpoint
+            .as_ref()
+            .map(|path| (path, self.checkpoint_settings()));
+        let mut state = self.initial_state(checkpoint.as_ref());
+        for pixel in &mut state.pixels {
+            pixel.done = self.pixel_done(pixel);
+        }
+
+        let deadline = self.time_budget.map(|budget| Instant::now() + budget);
+        let should_stop = || {
+            self.cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+                || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        };
+
+        let remaining_passes = passes.saturating_sub(state.pass).max(1);
+        let pb = ProgressBar::new(state.pixels.len() as u64 * remaining_passes as u64);
+
+        let mut last_checkpoint = Instant::now();
+        let mut buffer = loop {
+            state.pass += 1;
+            let pass_end = (state.pass as i32 * samples_per_pass).min(max_samples);
+
+            let mut stopped = false;
+            'pixels: for (index, pixel) in state.pixels.iter_mut().enumerate() {
+                pb.inc(1);
+                if pixel.done {
+                    continue;
+                }
+                let x = index as u32 % self.image_width;
+                let y = index as u32 / self.image_width;
+
+                while pixel.samples < pass_end {
+                    if should_stop() {
+                        stopped = true;
+                        break 'pixels;
+                    }
+                    sampler.start_pixel_sample(x, y, pixel.samples as u32);
+                    let aov = selection.any().then_some(&mut pixel.aov);
+                    let sample_color =
+                        self.sample_pixel(x, y, world, sampler.as_mut(), &mut state.film, aov);
+                    pixel.samples += 1;
+
+                    if self.adaptive.is_some() {
+                        let lum = color::luminance(sample_color).max(0.0);
+                        pixel.variance.add(lum / (1.0 + lum));
+                    }
+                    if self.pixel_done(pixel) {
+                        pixel.done = true;
+                        break;
+                    }
+                }
             }
-            let result_color = color * self.pixel_samples_scale;
-            *pixel = Rgb(color::color_rgb(result_color));
-            pb.inc(1);
+
+            let image = state.film.to_image();
+            if let Some(path) = &self.snapshot {
+                if let Err(err) = output::save(&image, &self.display, self.alpha_mode, path) {
+                    eprintln!("Failed to write snapshot {}: {err}", path.display());
+                }
+            }
+            let finished = state.pixels.iter().all(|pixel| pixel.done);
+            let flow = on_pass(&Pass {
+                index: state.pass,
+                samples_per_pixel: pass_end,
+                image: &image,
+            });
+            let done = finished || stopped || flow.is_break();
+
+            if let Some((path, settings)) = &checkpoint {
+                if done || last_checkpoint.elapsed() >= self.checkpoint_interval {
+                    if let Err(err) = checkpoint::save(path, settings, &state) {
+                        eprintln!("Failed to write checkpoint {}: {err}", path.display());
+                    }
+                    last_checkpoint = Instant::now();
+                }
+            }
+            if done {
+                break image;
+            }
+        };
+
+        for (index, pixel) in state.pixels.iter().enumerate() {
+            let x = index as u32 % self.image_width;
+            let y = index as u32 / self.image_width;
+            aovs.write_pixel(x, y, &pixel.aov, pixel.samples as u32);
         }
 
-        //for j in 0..self.image_height {
-        //    eprint!("\rScanlines remaining: {} ", self.image_height - j);
-        //    for i in 0..self.image_width {
-        //        let mut color = Color::new(0.0, 0.0, 0.0);
-        //        for sample in 0..self.samples_per_pixel {
-        //            let r = self.get_ray(i, j);
-        //            color += Self::ray_color(&r, self.max_depth, world);
-        //        }
-        //        let mid_result = color * self.pixel_samples_scale;
-        //        result.push_str(&color::color_string(mid_result));
-        //    }
-        //}
+        if let Some(denoiser) = &self.denoiser {
+            buffer = denoiser.apply(&buffer, aovs.albedo.as_ref(), aovs.normal.as_ref());
+            if !self.aovs.albedo {
+                aovs.albedo = None;
+            }
+            if !self.aovs.normal {
+                aovs.normal = None;
+            }
+        }
+
+        eprintln!("Done");
+        (buffer, aovs)
+    }
+
+    /// A fresh render state, or the one saved at the checkpoint path.
+    fn initial_state(&self, checkpoint: Option<&(&PathBuf, checkpoint::Settings)>) -> RenderState {
+        let fresh = || RenderState {
+            pass: 0,
+            film: Film::new(self.image_width, self.image_height, self.filter),
+            pixels: (0..self.image_width * self.image_height)
+                .map(|_| PixelState::default())
+                .collect(),
+        };
+
+        let Some((path, settings)) = checkpoint.filter(|(path, _)| path.exists()) else {
+            return fresh();
+        };
+        match checkpoint::load(path, settings) {
+            Ok(state) => {
+                eprintln!("Resuming from {} after pass {}", path.display(), state.pass);
+                state
+            }
+            Err(err) => {
+                eprintln!("Ignoring checkpoint {}: {err}", path.display());
+                fresh()
+            }
+        }
+    }
 
-        eprint!("Done\n");
-        buffer
+    fn checkpoint_settings(&self) -> checkpoint::Settings {
+        checkpoint::Settings {
+            seed: self.seed,
+            width: self.image_width,
+            height: self.image_height,
+            sampler: self.sampler,
+            filter: self.filter,
+            spectral: self.spectral,
+            max_depth: self.max_depth,
+            samples_per_pixel: self.samples_per_pixel,
+            adaptive: self.adaptive,
+            samples_per_pass: self.samples_per_pass,
+            view: checkpoint::describe(&(
+                self.center,
+                self.right,
+                self.up,
+                self.forward,
+                self.projection,
+                &self.lens,
+                self.stereo,
+                &self.environment,
+            )),
+        }
     }
 
-    fn ray_color(&self, ray: &Ray, depth: i32, world: &dyn Hittable) -> Color {
+    /// Takes one camera sample through pixel (x, y), splats it into `film`
+    /// and adds its first hit to `aov`. Returns the sample's color.
+    fn sample_pixel(
+        &self,
+        x: u32,
+        y: u32,
+        world: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+        film: &mut Film,
+        aov: Option<&mut AovPixel>,
+    ) -> Color {
+        let offset = Self::sample_square(sampler);
+        let (mut r, weight) = match self.get_ray(x, y, &offset, sampler) {
+            CameraRay::Ray(r, weight) => (r, weight),
+            missed => {
+                let alpha = match missed {
+                    CameraRay::Blocked => 1.0,
+                    _ => 0.0,
+                };
+                film.add_sample(x, y, (offset.x, offset.y), Color::default(), alpha);
+                return Color::default();
+            }
+        };
+        let hit = world.hit(&r, &Interval::new(0.001, f64::INFINITY));
+        if let Some(aov) = aov {
+            aov.add(&r, &self.forward, hit.as_ref());
+        }
+        if self.spectral {
+            r.wavelength = Some(spectrum::sample_wavelength(sampler.get_1d()));
+        }
+        let offset = (offset.x, offset.y);
+        let matte = hit.as_ref().and_then(|rec| Some((rec, rec.mat.matte()?)));
+        match matte {
+            Some((_, Matte::Holdout)) => {
+                film.add_sample(x, y, offset, Color::default(), 0.0);
+                Color::default()
+            }
+            Some((rec, Matte::ShadowCatcher)) => {
+                let (lit, unshadowed) = self.catcher_light(&r, rec, world, sampler);
+                film.add_shadow(x, y, offset, lit, unshadowed);
+                Color::new(lit, lit, lit)
+            }
+            None => {
+                let alpha = match hit {
+                    Some(_) => 1.0,
+                    None => self.environment.alpha(&r.direction),
+                };
+                let radiance = if self.max_depth > 0 {
+                    self.shade(&r, hit.as_ref(), self.max_depth, world, sampler) * weight
+                } else {
+                    Color::default()
+                };
+                let sample_color = match r.wavelength {
+                    Some(lambda) => spectrum::spectral_to_rgb(radiance.x, lambda),
+                    None => radiance,
+                };
+                film.add_sample(x, y, offset, sample_color * alpha, alpha);
+                sample_color
+            }
+        }
+    }
+
+    /// Whether `pixel` has taken all the samples it is going to get.
+    fn pixel_done(&self, pixel: &PixelState) -> bool {
+        match &self.adaptive {
+            Some(adaptive) => {
+                pixel.samples >= adaptive.max_samples
+                    || (pixel.samples >= adaptive.min_samples
+                        && pixel.variance.standard_error() < adaptive.noise_threshold)
+            }
+            None => pixel.samples >= self.samples_per_pixel,
+        }
+    }
+
+    fn ray_color(
+        &self,
+        ray: &Ray,
+        depth: i32,
+        world: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
         if depth <= 0 {
             return Color::new(0.0, 0.0, 0.0);
         }
 
-        match world.hit(&ray, &Interval::new(0.001, std::f64::INFINITY)) {
+        let hit = world.hit(ray, &Interval::new(0.001, f64::INFINITY));
+        self.shade(ray, hit.as_ref(), depth, world, sampler)
+    }
+
+    /// Radiance arriving back along `ray`, which has `hit` (or missed) the
+    /// scene with `depth` > 0 bounces left.
+    fn shade(
+        &self,
+        ray: &Ray,
+        hit: Option<&HitRecord>,
+        depth: i32,
+        world: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
+        match hit {
             Some(hit) => {
-                if let Some((scattered, attenuation)) = hit.mat.scatter(ray, &hit) {
-                    attenuation * self.ray_color(&scattered, depth - 1, world)
-                } else {
-                    Color::default()
+                sampler.start_bounce((self.max_depth - depth) as u32);
+                let emitted = Self::to_ray_space(ray, hit.mat.emitted(hit));
+                match self.bounce(ray, hit, sampler) {
+                    Some((scattered, attenuation)) => {
+                        emitted
+                            + attenuation * self.ray_color(&scattered, depth - 1, world, sampler)
+                    }
+                    None => emitted,
                 }
             }
-            None => {
-                let unit_direction = ray.direction.unit_vector();
-                let a = 0.5 * (unit_direction.y + 1.0);
-                let color = Vec3::new(1.0, 1.0, 1.0) * (1.0 - a) + self.sky_color * a;
-                color
-            }
+            None => Self::to_ray_space(ray, self.environment.value(&ray.direction)),
+        }
+    }
+
+    /// Scatters `ray` off `hit`, returning the next ray on the same
+    /// wavelength and the attenuation in ray space.
+    fn bounce(
+        &self,
+        ray: &Ray,
+        hit: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let (scattered, attenuation) = hit.mat.scatter(ray, hit, sampler)?;
+        let (mut scattered, attenuation) =
+            self.sample_environment(ray, hit, scattered, attenuation, sampler)?;
+        scattered.wavelength = ray.wavelength;
+        Some((scattered, Self::to_ray_space(ray, attenuation)))
+    }
+
+    /// Luminance of the light bouncing off a shadow catcher that a camera
+    /// ray hit, as rendered and as it would be if only the environment were
+    /// there, estimated along the same bounce.
+    fn catcher_light(
+        &self,
+        ray: &Ray,
+        hit: &HitRecord,
+        world: &dyn Hittable,
+        sampler: &mut dyn Sampler,
+    ) -> (f64, f64) {
+        sampler.start_bounce(0);
+        let Some((scattered, attenuation)) = self.bounce(ray, hit, sampler) else {
+            return (0.0, 0.0);
+        };
+        let lit = attenuation * self.ray_color(&scattered, self.max_depth - 1, world, sampler);
+        let unshadowed = attenuation
+            * Self::to_ray_space(&scattered, self.environment.value(&scattered.direction));
+        (color::luminance(lit), color::luminance(unshadowed))
+    }
+
+    /// When the environment can be sampled, redraws the bounce of a material
+    /// that reports its `scattering_pdf` from an even mix of that pdf and the
+    /// environment's, and reweights it to match. Returns `None` when the
+    /// redrawn direction can't scatter.
+    fn sample_environment(
+        &self,
+        ray: &Ray,
+        hit: &HitRecord,
+        scattered: Ray,
+        attenuation: Color,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        if hit.mat.scattering_pdf(ray, hit, &scattered) <= 0.0 {
+            return Some((scattered, attenuation));
+        }
+        let use_environment = sampler.get_1d() < 0.5;
+        let Some((direction, _)) = self.environment.sample(sampler.get_2d()) else {
+            return Some((scattered, attenuation));
+        };
+
+        let scattered = if use_environment {
+            Ray::new(hit.p, direction)
+        } else {
+            scattered
+        };
+        let scattering_pdf = hit.mat.scattering_pdf(ray, hit, &scattered);
+        if scattering_pdf <= 0.0 {
+            return None;
         }
+        let pdf = 0.5 * scattering_pdf + 0.5 * self.environment.pdf(&scattered.direction);
+        Some((scattered, attenuation * (scattering_pdf / pdf)))
     }
 
-    fn get_ray(&self, i: i32, j: i32) -> Ray {
-        let offset = Self::sample_square();
-        let pixel_sample = self.pixel00_loc
-            + (self.pixel_delta_u * (i as f64 + offset.x))
-            + (self.pixel_delta_v * (j as f64 + offset.y));
+    /// RGB colors are used as-is on RGB rays. Spectral rays upsample them to
+    /// the ray's wavelength and carry the result as a grey color.
+    fn to_ray_space(ray: &Ray, color: Color) -> Color {
+        match ray.wavelength {
+            Some(lambda) => {
+                let value = spectrum::rgb_to_spectrum(color, lambda);
+                Color::new(value, value, value)
+            }
+            None => color,
+        }
+    }
 
-        // if i == 0 && j == 0 {
-        //     println!("{:?}", pixel_sample);
-        // }
+    /// Ray through pixel (i, j), displaced from its center by `offset`
+    /// pixels. A lens draws the point it passes through from `sampler`.
+    fn get_ray(&self, i: u32, j: u32, offset: &Vec3, sampler: &mut dyn Sampler) -> CameraRay {
+        // Pixel and size of the view it belongs to, and for stereo, the eye.
+        let (mut i, mut j) = (i, j);
+        let (mut width, mut height) = (self.image_width, self.image_height);
+        let mut side = None;
+        if let Some(stereo) = &self.stereo {
+            let (along, size) = match stereo.layout {
+                StereoLayout::SideBySide => (&mut i, &mut width),
+                StereoLayout::OverUnder => (&mut j, &mut height),
+            };
+            *size /= 2;
+            side = Some(if *along < *size { -1.0 } else { 1.0 });
+            if *along >= *size {
+                *along -= *size;
+            }
+        }
 
-        let ray_origin = self.center;
-        let ray_direction = pixel_sample - ray_origin;
+        let s = (i as f64 + 0.5 + offset.x) / width as f64;
+        let t = (j as f64 + 0.5 + offset.y) / height as f64;
+        let aspect_ratio = width as f64 / height as f64;
+        let (mut ray, weight) = match &self.lens {
+            Some(lens) => match lens.ray((s, t), aspect_ratio, sampler.get_2d()) {
+                Some((origin, direction, weight)) => ((origin, direction), weight),
+                None => return CameraRay::Blocked,
+            },
+            None => match self.projection.ray((s, t), aspect_ratio) {
+                Some(ray) => (ray, 1.0),
+                None => return CameraRay::Outside,
+            },
+        };
+        if let (Some(stereo), Some(side)) = (&self.stereo, side) {
+            // A lens focuses by itself, so its eyes stay parallel.
+            let stereo = match self.lens {
+                Some(_) => Stereo {
+                    convergence: f64::INFINITY,
+                    ..*stereo
+                },
+                None => *stereo,
+            };
+            ray = stereo.eye_ray(&self.projection, side, ray);
+        }
+        let (origin, direction) = ray;
 
-        Ray::new(ray_origin, ray_direction)
+        let to_world = |v: Vec3| self.right * v.x + self.up * v.y - self.forward * v.z;
+        CameraRay::Ray(
+            Ray::new(self.center + to_world(origin), to_world(direction)),
+            weight,
+        )
     }
 
-    fn sample_square() -> Vec3 {
-        Vec3::new(random_f64() - 0.5, random_f64() - 0.5, 0.0)
+    fn sample_square(sampler: &mut dyn Sampler) -> Vec3 {
+        let (u, v) = sampler.get_2d();
+        Vec3::new(u - 0.5, v - 0.5, 0.0)
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct CameraBuilder {
     aspect_ratio: f64,
     image_width: u32,
     center: Point3,
+    look_at: Option<Point3>,
+    up: Vec3,
+    projection: Projection,
     max_depth: i32,
     samples_per_pixel: i32,
-    sky_color: Color,
+    environment: Box<dyn Environment>,
+    spectral: bool,
+    display: DisplayTransform,
+    alpha_mode: AlphaMode,
+    aovs: AovSelection,
+    denoiser: Option<Denoiser>,
+    adaptive: Option<AdaptiveSampling>,
+    sampler: SamplerKind,
+    seed: u64,
+    filter: Filter,
+    samples_per_pass: Option<i32>,
+    snapshot: Option<PathBuf>,
+    checkpoint: Option<PathBuf>,
+    checkpoint_interval: Duration,
+    time_budget: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+    stereo: Option<Stereo>,
+    lens: Option<RealisticLens>,
+}
+
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CameraBuilder {
@@ -118,17 +677,57 @@ impl CameraBuilder {
         let aspect_ratio = 16.0 / 9.0;
         let image_width = 400;
         let center = Vec3::new(0.0, 0.0, 0.0);
+        let look_at = None;
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let projection = Projection::default();
         let max_depth = 10;
         let samples_per_pixel = 10;
-        let sky_color = Color::new(0.5, 0.7, 1.0);
+        let environment: Box<dyn Environment> = Box::new(Gradient::default());
+        let spectral = false;
+        let display = DisplayTransform::default();
+        let alpha_mode = AlphaMode::default();
+        let aovs = AovSelection::default();
+        let denoiser = None;
+        let adaptive = None;
+        let sampler = SamplerKind::default();
+        let seed = 0;
+        let filter = Filter::default();
+        let samples_per_pass = None;
+        let snapshot = None;
+        let checkpoint = None;
+        let checkpoint_interval = Duration::from_secs(10);
+        let time_budget = None;
+        let cancellation = None;
+        let stereo = None;
+        let lens = None;
 
         Self {
             aspect_ratio,
             image_width,
             center,
+            look_at,
+            up,
+            projection,
             max_depth,
             samples_per_pixel,
-            sky_color,
+            environment,
+            spectral,
+            display,
+            alpha_mode,
+            aovs,
+            denoiser,
+            adaptive,
+            sampler,
+            seed,
+            filter,
+            samples_per_pass,
+            snapshot,
+            checkpoint,
+            checkpoint_interval,
+            time_budget,
+            cancellation,
+            stereo,
+            lens,
         }
     }
 
@@ -147,6 +746,27 @@ impl CameraBuilder {
         self
     }
 
+    /// Point the camera looks at. Unless set, the camera looks down -Z from
+    /// wherever its center is. Panics in `build` if it is the center itself.
+    pub fn look_at(mut self, look_at: Point3) -> Self {
+        self.look_at = Some(look_at);
+        self
+    }
+
+    /// Direction that comes out as up in the image, +Y by default. It
+    /// only needs to be roughly perpendicular to the viewing direction; when
+    /// it is parallel to it, the camera picks a perpendicular up itself.
+    pub fn up(mut self, up: Vec3) -> Self {
+        self.up = up;
+        self
+    }
+
+    /// Perspective with a 90° vertical field of view unless set.
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
     pub fn max_depth(mut self, max_depth: i32) -> Self {
         self.max_depth = max_depth;
         self
@@ -157,8 +777,149 @@ impl CameraBuilder {
         self
     }
 
+    /// Shorthand for a `Gradient` environment from white to `sky_color`.
     pub fn sky_color(mut self, sky_color: Color) -> Self {
-        self.sky_color = sky_color;
+        self.environment = Box::new(Gradient::new(sky_color));
+        self
+    }
+
+    /// What rays that leave the scene see, and what lights it. Defaults to
+    /// the white to blue `Gradient`.
+    pub fn environment(mut self, environment: impl Environment + 'static) -> Self {
+        self.environment = Box::new(environment);
+        self
+    }
+
+    /// Trace one wavelength per sample instead of RGB, so dispersive
+    /// materials can split light.
+    pub fn spectral(mut self, spectral: bool) -> Self {
+        self.spectral = spectral;
+        self
+    }
+
+    /// Exposure adjustment in stops (EV) for display output.
+    pub fn exposure(mut self, exposure: f64) -> Self {
+        self.display.exposure = exposure;
+        self
+    }
+
+    pub fn tone_mapper(mut self, tone_mapper: ToneMapper) -> Self {
+        self.display.tone_mapper = tone_mapper;
+        self
+    }
+
+    pub fn transfer_function(mut self, transfer: TransferFunction) -> Self {
+        self.display.transfer = transfer;
+        self
+    }
+
+    /// Whether snapshots store color premultiplied by alpha or not. Renders
+    /// are only partly transparent with a `Transparent` environment, holdouts
+    /// or shadow catchers.
+    pub fn alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Feature buffers to produce alongside the beauty pass, see
+    /// `Camera::render_with_aovs`.
+    pub fn aovs(mut self, aovs: AovSelection) -> Self {
+        self.aovs = aovs;
+        self
+    }
+
+    /// Filter the finished framebuffer with `denoiser`.
+    pub fn denoiser(mut self, denoiser: Denoiser) -> Self {
+        self.denoiser = Some(denoiser);
+        self
+    }
+
+    /// Replace the fixed `samples_per_pixel` with noise-driven sample
+    /// counts. Enable the `sample_count` AOV to see where samples went.
+    pub fn adaptive_sampling(mut self, adaptive: AdaptiveSampling) -> Self {
+        self.adaptive = Some(adaptive);
+        self
+    }
+
+    pub fn sampler(mut self, sampler: SamplerKind) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Seed for the sampler. Renders with the same seed and settings are
+    /// identical.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Pixel reconstruction filter. Filters wider than half a pixel spread
+    /// each sample over the neighboring pixels.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Render progressively, `samples_per_pass` samples per pixel at a
+    /// time, see `Camera::render_progressive`.
+    pub fn samples_per_pass(mut self, samples_per_pass: i32) -> Self {
+        self.samples_per_pass = Some(samples_per_pass);
+        self
+    }
+
+    /// Write the image so far to `path` after every pass. HDR extensions
+    /// get linear radiance, anything else the display transformed image.
+    pub fn snapshot(mut self, path: impl Into<PathBuf>) -> Self {
+        self.snapshot = Some(path.into());
+        self
+    }
+
+    /// Save the render state to `path` after a pass every
+    /// `checkpoint_interval`, and when the render stops. Unless
+    /// `samples_per_pass` is set, passes are then one sample per pixel. If
+    /// the file already exists when rendering starts and was saved with the
+    /// same settings, the render picks up from it, so a killed render can be
+    /// resumed, or a finished one continued with a higher
+    /// `samples_per_pixel`.
+    pub fn checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Least time between two checkpoints, 10 seconds by default.
+    pub fn checkpoint_interval(mut self, interval: Duration) -> Self {
+        self.checkpoint_interval = interval;
+        self
+    }
+
+    /// Stop rendering once `budget` of wall-clock time has passed. Unless
+    /// `samples_per_pass` is set, the render then goes in passes of one
+    /// sample per pixel.
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Stop rendering when `token` is cancelled, from any thread. Passes
+    /// default to one sample per pixel, as with `time_budget`.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Render a stereo pair. `image_width` and `aspect_ratio` then apply to
+    /// each eye, and the image is twice as wide or tall depending on the
+    /// layout.
+    pub fn stereo(mut self, stereo: Stereo) -> Self {
+        self.stereo = Some(stereo);
+        self
+    }
+
+    /// Trace camera rays through a realistic lens instead of using the
+    /// projection. Stereo still offsets the eyes, but their convergence is
+    /// left to the lens focus.
+    pub fn lens(mut self, lens: RealisticLens) -> Self {
+        self.lens = Some(lens);
         self
     }
 
@@ -167,41 +928,88 @@ impl CameraBuilder {
             aspect_ratio,
             image_width,
             center,
+            look_at,
+            up,
+            projection,
             max_depth,
             samples_per_pixel,
-            sky_color,
+            environment,
+            spectral,
+            display,
+            alpha_mode,
+            aovs,
+            denoiser,
+            adaptive,
+            sampler,
+            seed,
+            filter,
+            samples_per_pass,
+            snapshot,
+            checkpoint,
+            checkpoint_interval,
+            time_budget,
+            cancellation,
+            stereo,
+            lens,
         } = self;
 
-        let image_height = ((image_width as f64 / aspect_ratio) as u32).max(1);
-
-        let focal_length = 1.0;
-        let viewport_height = 2.0;
-        let viewport_width = viewport_height * (image_width as f64 / image_height as f64);
-
-        let viewport_u = Vec3::new(viewport_width, 0.0, 0.0);
-        let viewport_v = Vec3::new(0.0, -viewport_height, 0.0);
-
-        let pixel_delta_u = viewport_u / image_width as f64;
-        let pixel_delta_v = viewport_v / image_height as f64;
-
-        let viewport_upper_left =
-            center - viewport_u / 2.0 - viewport_v / 2.0 - Vec3::new(0.0, 0.0, focal_length);
-        let pixel00_loc = viewport_upper_left + (pixel_delta_u + pixel_delta_v) * 0.5;
+        let mut image_width = image_width;
+        let mut image_height = ((image_width as f64 / aspect_ratio) as u32).max(1);
+        match stereo.map(|stereo| stereo.layout) {
+            Some(StereoLayout::SideBySide) => image_width *= 2,
+            Some(StereoLayout::OverUnder) => image_height *= 2,
+            None => {}
+        }
 
-        let pixel_samples_scale = 1.0 / samples_per_pixel as f64;
+        let view = look_at.map_or(Vec3::new(0.0, 0.0, -1.0), |look_at| look_at - center);
+        assert!(
+            !view.near_zero(),
+            "camera look_at {look_at:?} coincides with its center"
+        );
+        let forward = view.unit_vector();
+        let mut right = forward.cross(&up);
+        if right.near_zero() {
+            // Looking straight along `up`: any perpendicular will do, so take
+            // the axis furthest from the viewing direction.
+            let axis = if forward.x.abs() < 0.5 {
+                Vec3::new(1.0, 0.0, 0.0)
+            } else {
+                Vec3::new(0.0, 1.0, 0.0)
+            };
+            right = axis.cross(&forward);
+        }
+        let right = right.unit_vector();
+        let up = right.cross(&forward);
 
         Camera {
             aspect_ratio,
             image_width,
             image_height,
             center,
+            projection,
             max_depth,
-            sky_color,
+            environment,
+            spectral,
+            display,
+            alpha_mode,
+            aovs,
+            denoiser,
             samples_per_pixel,
-            pixel00_loc,
-            pixel_delta_u,
-            pixel_delta_v,
-            pixel_samples_scale,
+            adaptive,
+            sampler,
+            seed,
+            filter,
+            samples_per_pass,
+            snapshot,
+            checkpoint,
+            checkpoint_interval,
+            time_budget,
+            cancellation,
+            stereo,
+            lens: lens.as_ref().map(LensSystem::new),
+            right,
+            up,
+            forward,
         }
     }
 }
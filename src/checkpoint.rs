@@ -0,0 +1,324 @@
+use std::{
+    fmt::{self, Debug},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    aov::AovPixel,
+    camera::{AdaptiveSampling, PixelState, RenderState, RunningVariance},
+    color::Color,
+    film::{Film, Shadow},
+    filter::Filter,
+    sampler::SamplerKind,
+};
+
+const MAGIC: &[u8; 4] = b"RTCK";
+const VERSION: u32 = 5;
+
+/// Camera settings that decide which samples a render takes and how they
+/// add up. A checkpoint only resumes a render with the same settings, except
+/// that a render may continue one saved with fewer `samples_per_pixel` when
+/// the sampler doesn't depend on that count.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Settings {
+    pub(crate) seed: u64,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) sampler: SamplerKind,
+    pub(crate) filter: Filter,
+    pub(crate) spectral: bool,
+    pub(crate) max_depth: i32,
+    pub(crate) samples_per_pixel: i32,
+    pub(crate) adaptive: Option<AdaptiveSampling>,
+    pub(crate) samples_per_pass: Option<i32>,
+    /// `describe` of everything that decides where the camera rays go and
+    /// what they see when they leave the scene.
+    pub(crate) view: u64,
+}
+
+impl Settings {
+    /// Whether a render with these settings can pick up a checkpoint saved
+    /// with `saved`.
+    fn resumes(&self, saved: &Settings) -> bool {
+        let more_samples = match self.sampler {
+            SamplerKind::Stratified => saved.samples_per_pixel == self.samples_per_pixel,
+            _ => saved.samples_per_pixel <= self.samples_per_pixel,
+        };
+        more_samples
+            && Settings {
+                samples_per_pixel: self.samples_per_pixel,
+                ..*saved
+            } == *self
+    }
+}
+
+/// Writes the render state to `path`. The samplers are counter based, so
+/// the seed plus each pixel's sample count is all the random state there
+/// is: a resumed render draws exactly the samples an uninterrupted one
+/// would have.
+///
+/// The file is written next to `path` first and then renamed over it, so a
+/// crash mid-write leaves the previous checkpoint intact.
+pub(crate) fn save(path: &Path, settings: &Settings, state: &RenderState) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut w = BufWriter::new(File::create(&tmp)?);
+
+    w.write_all(MAGIC)?;
+    write_u32(&mut w, VERSION)?;
+    write_settings(&mut w, settings)?;
+    write_u32(&mut w, state.pass)?;
+
+    for (index, pixel) in state.pixels.iter().enumerate() {
+        write_color(&mut w, state.film.sum[index])?;
+        write_f64(&mut w, state.film.alpha[index])?;
+        write_f64(&mut w, state.film.weight[index])?;
+        let shadow = &state.film.shadow[index];
+        for value in [shadow.weight, shadow.lit, shadow.unshadowed] {
+            write_f64(&mut w, value)?;
+        }
+        write_u32(&mut w, pixel.samples as u32)?;
+
+        let variance = &pixel.variance;
+        for value in [variance.count, variance.mean, variance.m2] {
+            write_f64(&mut w, value)?;
+        }
+
+        let aov = &pixel.aov;
+        write_color(&mut w, aov.normal)?;
+        write_color(&mut w, aov.albedo)?;
+        match aov.first {
+            Some((depth, position, id)) => {
+                w.write_all(&[1])?;
+                write_f64(&mut w, depth)?;
+                write_color(&mut w, position)?;
+                write_f64(&mut w, id)?;
+            }
+            None => w.write_all(&[0])?,
+        }
+    }
+
+    w.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+    fs::rename(tmp, path)
+}
+
+/// Reads a checkpoint written by `save`, failing with `InvalidData` if it
+/// belongs to a render with other settings, see `Settings`.
+pub(crate) fn load(path: &Path, settings: &Settings) -> io::Result<RenderState> {
+    let mut r = BufReader::new(File::open(path)?);
+
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC || read_u32(&mut r)? != VERSION {
+        return Err(invalid("not a checkpoint file"));
+    }
+    let saved = read_settings(&mut r)?;
+    if !settings.resumes(&saved) {
+        return Err(invalid(&format!(
+            "saved with different settings: {saved:?}"
+        )));
+    }
+    let pass = read_u32(&mut r)?;
+
+    let Settings { width, height, .. } = *settings;
+    let mut film = Film::new(width, height, settings.filter);
+    let mut pixels = Vec::with_capacity((width * height) as usize);
+    for index in 0..(width * height) as usize {
+        film.sum[index] = read_color(&mut r)?;
+        film.alpha[index] = read_f64(&mut r)?;
+        film.weight[index] = read_f64(&mut r)?;
+        film.shadow[index] = Shadow {
+            weight: read_f64(&mut r)?,
+            lit: read_f64(&mut r)?,
+            unshadowed: read_f64(&mut r)?,
+        };
+        let samples = read_u32(&mut r)? as i32;
+
+        let variance = RunningVariance {
+            count: read_f64(&mut r)?,
+            mean: read_f64(&mut r)?,
+            m2: read_f64(&mut r)?,
+        };
+
+        let normal = read_color(&mut r)?;
+        let albedo = read_color(&mut r)?;
+        let mut flag = [0];
+        r.read_exact(&mut flag)?;
+        let first = match flag[0] {
+            0 => None,
+            _ => Some((read_f64(&mut r)?, read_color(&mut r)?, read_f64(&mut r)?)),
+        };
+
+        pixels.push(PixelState {
+            samples,
+            aov: AovPixel {
+                normal,
+                albedo,
+                first,
+            },
+            variance,
+            done: false,
+        });
+    }
+
+    Ok(RenderState { pass, film, pixels })
+}
+
+fn write_settings(w: &mut impl Write, settings: &Settings) -> io::Result<()> {
+    w.write_all(&settings.seed.to_le_bytes())?;
+    write_u32(w, settings.width)?;
+    write_u32(w, settings.height)?;
+
+    let sampler = match settings.sampler {
+        SamplerKind::Independent => 0,
+        SamplerKind::Stratified => 1,
+        SamplerKind::Halton => 2,
+        SamplerKind::Sobol => 3,
+    };
+    let (filter, parameters) = match settings.filter {
+        Filter::Box { radius } => (0, [radius, 0.0, 0.0]),
+        Filter::Tent { radius } => (1, [radius, 0.0, 0.0]),
+        Filter::Gaussian { radius, sigma } => (2, [radius, sigma, 0.0]),
+        Filter::Mitchell { radius, b, c } => (3, [radius, b, c]),
+        Filter::Lanczos { radius } => (4, [radius, 0.0, 0.0]),
+    };
+    w.write_all(&[sampler, filter, settings.spectral as u8])?;
+    for value in parameters {
+        write_f64(w, value)?;
+    }
+
+    write_u32(w, settings.max_depth as u32)?;
+    write_u32(w, settings.samples_per_pixel as u32)?;
+    match settings.adaptive {
+        Some(adaptive) => {
+            w.write_all(&[1])?;
+            write_u32(w, adaptive.min_samples as u32)?;
+            write_u32(w, adaptive.max_samples as u32)?;
+            write_f64(w, adaptive.noise_threshold)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+    // Zero stands for no `samples_per_pass`, which is never a valid one.
+    write_u32(w, settings.samples_per_pass.unwrap_or(0) as u32)?;
+    w.write_all(&settings.view.to_le_bytes())
+}
+
+fn read_settings(r: &mut impl Read) -> io::Result<Settings> {
+    let mut seed = [0; 8];
+    r.read_exact(&mut seed)?;
+    let (width, height) = (read_u32(r)?, read_u32(r)?);
+
+    let mut tags = [0; 3];
+    r.read_exact(&mut tags)?;
+    let [sampler, filter, spectral] = tags;
+    let sampler = match sampler {
+        0 => SamplerKind::Independent,
+        1 => SamplerKind::Stratified,
+        2 => SamplerKind::Halton,
+        3 => SamplerKind::Sobol,
+        _ => return Err(invalid("unknown sampler")),
+    };
+    let [radius, p1, p2] = [read_f64(r)?, read_f64(r)?, read_f64(r)?];
+    let filter = match filter {
+        0 => Filter::Box { radius },
+        1 => Filter::Tent { radius },
+        2 => Filter::Gaussian { radius, sigma: p1 },
+        3 => Filter::Mitchell {
+            radius,
+            b: p1,
+            c: p2,
+        },
+        4 => Filter::Lanczos { radius },
+        _ => return Err(invalid("unknown filter")),
+    };
+
+    let max_depth = read_u32(r)? as i32;
+    let samples_per_pixel = read_u32(r)? as i32;
+    let mut flag = [0];
+    r.read_exact(&mut flag)?;
+    let adaptive = match flag[0] {
+        0 => None,
+        _ => Some(AdaptiveSampling {
+            min_samples: read_u32(r)? as i32,
+            max_samples: read_u32(r)? as i32,
+            noise_threshold: read_f64(r)?,
+        }),
+    };
+    let samples_per_pass = match read_u32(r)? as i32 {
+        0 => None,
+        samples_per_pass => Some(samples_per_pass),
+    };
+    let mut view = [0; 8];
+    r.read_exact(&mut view)?;
+
+    Ok(Settings {
+        seed: u64::from_le_bytes(seed),
+        width,
+        height,
+        sampler,
+        filter,
+        spectral: spectral != 0,
+        max_depth,
+        samples_per_pixel,
+        adaptive,
+        samples_per_pass,
+        view: u64::from_le_bytes(view),
+    })
+}
+
+/// 64-bit FNV-1a hash of the `Debug` output of `value`. Unlike the standard
+/// library's hasher, it stays the same across Rust versions, so checkpoints
+/// keep resuming after an upgrade.
+pub(crate) fn describe(value: &impl Debug) -> u64 {
+    struct Fnv(u64);
+
+    impl fmt::Write for Fnv {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for byte in s.bytes() {
+                self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+            Ok(())
+        }
+    }
+
+    let mut hash = Fnv(0xcbf2_9ce4_8422_2325);
+    fmt::write(&mut hash, format_args!("{value:?}")).expect("Debug output can't fail");
+    hash.0
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u32(w: &mut impl Write, value: u32) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_f64(w: &mut impl Write, value: f64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_color(w: &mut impl Write, color: Color) -> io::Result<()> {
+    for value in [color.x, color.y, color.z] {
+        write_f64(w, value)?;
+    }
+    Ok(())
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f64(r: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+fn read_color(r: &mut impl Read) -> io::Result<Color> {
+    Ok(Color::new(read_f64(r)?, read_f64(r)?, read_f64(r)?))
+}
@@ -1,10 +1,17 @@
+use image::Rgb;
+
 use crate::{interval::Interval, vec3::Vec3};
 
 pub type Color = Vec3;
 
+/// Relative luminance of a linear Rec. 709 color.
+pub fn luminance(color: Color) -> f64 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
 fn linear_to_gamma(color: Color) -> Color
 {
-    let mut result = color.clone();
+    let mut result = color;
     if color[0] > 0.0 {
         result[0] = color[0].sqrt();
     }
@@ -19,9 +26,12 @@ fn linear_to_gamma(color: Color) -> Color
 
 pub fn color_rgb(color: Color) -> [u8; 3]
 {
-    let Color {
-        x: r, y: g, z: b
-    } = linear_to_gamma(color);
+    quantize(linear_to_gamma(color))
+}
+
+/// Encoded color in [0, 1] to 8 bits per channel.
+pub fn quantize(color: Color) -> [u8; 3] {
+    let Color { x: r, y: g, z: b } = color;
 
     let intensity = Interval::new(0.000, 0.999);
     let ir = (256.0 * intensity.clamp(r)) as u8;
@@ -30,3 +40,12 @@ pub fn color_rgb(color: Color) -> [u8; 3]
 
     [ir, ig, ib]
 }
+
+pub fn color_rgb32f(color: Color) -> [f32; 3] {
+    [color.x as f32, color.y as f32, color.z as f32]
+}
+
+pub fn from_rgb32f(pixel: &Rgb<f32>) -> Color {
+    let [r, g, b] = pixel.0;
+    Color::new(r as f64, g as f64, b as f64)
+}
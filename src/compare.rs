@@ -0,0 +1,107 @@
+use image::{Rgb, RgbImage, RgbaImage};
+
+use crate::color::{self, Color};
+
+/// Side of the square windows SSIM compares, in pixels.
+const WINDOW: usize = 8;
+
+/// How far apart two 8-bit images of the same size are.
+#[derive(Clone, Copy, Debug)]
+pub struct Comparison {
+    /// Root mean square difference over all four channels, from 0 to 1.
+    pub rmse: f64,
+    /// Peak signal to noise ratio in dB: 20 log₁₀(1 / rmse). Infinite for
+    /// identical images.
+    pub psnr: f64,
+    /// Mean structural similarity (Wang et al. 2004) of the luma, from 1
+    /// for identical images down to 0 (or slightly below) for unrelated
+    /// ones. Unlike RMSE it cares more about lost edges and texture than
+    /// about a small uniform shift.
+    pub ssim: f64,
+}
+
+/// Compares `actual` with `expected`. Panics if their sizes differ.
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage) -> Comparison {
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "images to compare differ in size"
+    );
+
+    let squared: f64 = expected
+        .as_raw()
+        .iter()
+        .zip(actual.as_raw())
+        .map(|(&a, &b)| ((a as f64 - b as f64) / 255.0).powi(2))
+        .sum();
+    let rmse = (squared / expected.as_raw().len().max(1) as f64).sqrt();
+
+    Comparison {
+        rmse,
+        psnr: -20.0 * rmse.log10(),
+        ssim: ssim(&luma(expected), &luma(actual), expected.width() as usize),
+    }
+}
+
+/// Per-channel absolute difference, amplified by `gain`, for looking at
+/// where two images disagree.
+pub fn difference(expected: &RgbaImage, actual: &RgbaImage, gain: f64) -> RgbImage {
+    RgbImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let (a, b) = (expected.get_pixel(x, y).0, actual.get_pixel(x, y).0);
+        Rgb(std::array::from_fn(|c| {
+            (a[c].abs_diff(b[c]) as f64 * gain).min(255.0) as u8
+        }))
+    })
+}
+
+/// Luma of the encoded (not linear) values, from 0 to 1.
+fn luma(image: &RgbaImage) -> Vec<f64> {
+    image
+        .pixels()
+        .map(|p| {
+            let [r, g, b, _] = p.0;
+            color::luminance(Color::new(r as f64, g as f64, b as f64)) / 255.0
+        })
+        .collect()
+}
+
+/// Mean SSIM over `WINDOW`-sized windows half a window apart, or over the
+/// whole image when it is smaller than that.
+fn ssim(a: &[f64], b: &[f64], width: usize) -> f64 {
+    const C1: f64 = 0.01 * 0.01;
+    const C2: f64 = 0.03 * 0.03;
+
+    if a.is_empty() {
+        return 1.0;
+    }
+    let height = a.len() / width;
+    let (window_width, window_height) = (width.min(WINDOW), height.min(WINDOW));
+    let step = |window: usize| (window / 2).max(1);
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for y0 in (0..=height - window_height).step_by(step(window_height)) {
+        for x0 in (0..=width - window_width).step_by(step(window_width)) {
+            let pixels = || {
+                (y0..y0 + window_height)
+                    .flat_map(move |y| (x0..x0 + window_width).map(move |x| y * width + x))
+            };
+            let n = (window_width * window_height) as f64;
+            let mean_a = pixels().map(|i| a[i]).sum::<f64>() / n;
+            let mean_b = pixels().map(|i| b[i]).sum::<f64>() / n;
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for i in pixels() {
+                let (da, db) = (a[i] - mean_a, b[i] - mean_b);
+                var_a += da * da;
+                var_b += db * db;
+                covariance += da * db;
+            }
+            let (var_a, var_b, covariance) = (var_a / n, var_b / n, covariance / n);
+
+            total += (2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2)
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+    total / windows as f64
+}
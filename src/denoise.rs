@@ -0,0 +1,119 @@
+use image::{ImageBuffer, Pixel, Rgb32FImage};
+
+use crate::color::{self, Color};
+
+/// Joint bilateral denoiser for the linear framebuffer.
+///
+/// Neighbors are weighted by distance, by how close their (tone-compressed)
+/// color is, and, when the feature buffers are given, by how close their
+/// albedo and normal are. Edges that the noisy color can't resolve are still
+/// kept sharp as long as they show up in the albedo or normal AOVs.
+///
+/// With an albedo buffer the image is divided by albedo before filtering and
+/// multiplied back afterwards, so textures aren't blurred along with noise.
+///
+/// Works on RGB and RGBA images; only the color channels are filtered.
+#[derive(Clone, Copy, Debug)]
+pub struct Denoiser {
+    /// Half size of the filter window, in pixels.
+    pub radius: u32,
+    pub sigma_spatial: f64,
+    pub sigma_color: f64,
+    pub sigma_albedo: f64,
+    pub sigma_normal: f64,
+    /// Blend between the input (0.0) and the fully filtered image (1.0).
+    pub strength: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            radius: 5,
+            sigma_spatial: 3.0,
+            sigma_color: 0.4,
+            sigma_albedo: 0.1,
+            sigma_normal: 0.2,
+            strength: 1.0,
+        }
+    }
+}
+
+impl Denoiser {
+    pub fn apply<P: Pixel<Subpixel = f32>>(
+        &self,
+        image: &ImageBuffer<P, Vec<f32>>,
+        albedo: Option<&Rgb32FImage>,
+        normal: Option<&Rgb32FImage>,
+    ) -> ImageBuffer<P, Vec<f32>> {
+        let (width, height) = image.dimensions();
+        let at = |buffer: &Rgb32FImage, x: u32, y: u32| color::from_rgb32f(buffer.get_pixel(x, y));
+        let color_at = |x: u32, y: u32| {
+            let channels = image.get_pixel(x, y).channels();
+            Color::new(channels[0] as f64, channels[1] as f64, channels[2] as f64)
+        };
+        let demodulate = |x: u32, y: u32| match albedo {
+            Some(albedo) => color_at(x, y) / albedo_factor(at(albedo, x, y)),
+            None => color_at(x, y),
+        };
+        let compress = |c: Color| c / (c + 1.0);
+
+        // A zero sigma keeps only the center tap instead of making NaNs.
+        let falloff = |sigma: f64| -0.5 / (sigma * sigma).max(1e-12);
+        let spatial = falloff(self.sigma_spatial);
+        let r = self.radius as i64;
+
+        let mut output = image.clone();
+        for (x, y, pixel) in output.enumerate_pixels_mut() {
+            let center = demodulate(x, y);
+            let center_compressed = compress(center);
+
+            let mut sum = Color::default();
+            let mut weight_sum = 0.0;
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let nx = x as i64 + dx;
+                    let ny = y as i64 + dy;
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as u32, ny as u32);
+
+                    let sample = demodulate(nx, ny);
+                    let mut exponent = spatial * (dx * dx + dy * dy) as f64
+                        + falloff(self.sigma_color)
+                            * (compress(sample) - center_compressed).length_squared();
+                    if let Some(albedo) = albedo {
+                        exponent += falloff(self.sigma_albedo)
+                            * (at(albedo, nx, ny) - at(albedo, x, y)).length_squared();
+                    }
+                    if let Some(normal) = normal {
+                        exponent += falloff(self.sigma_normal)
+                            * (at(normal, nx, ny) - at(normal, x, y)).length_squared();
+                    }
+
+                    let weight = exponent.exp();
+                    sum += sample * weight;
+                    weight_sum += weight;
+                }
+            }
+
+            let mut filtered = sum / weight_sum;
+            if let Some(albedo) = albedo {
+                filtered = filtered * albedo_factor(at(albedo, x, y));
+            }
+            let original = color_at(x, y);
+            let result = original * (1.0 - self.strength) + filtered * self.strength;
+            pixel.channels_mut()[..3].copy_from_slice(&color::color_rgb32f(result));
+        }
+        output
+    }
+}
+
+/// Albedo to divide out of a pixel. Background pixels have no albedo and
+/// are filtered as they are.
+fn albedo_factor(albedo: Color) -> Color {
+    if albedo.length_squared() < 1e-12 {
+        return Color::new(1.0, 1.0, 1.0);
+    }
+    Color::new(albedo.x.max(1e-3), albedo.y.max(1e-3), albedo.z.max(1e-3))
+}
@@ -0,0 +1,350 @@
+use std::{f64::consts::PI, fmt::Debug, path::Path};
+
+use dyn_clone::DynClone;
+use image::{ImageResult, Rgb32FImage};
+
+use crate::{
+    color::{self, Color},
+    vec3::Vec3,
+};
+
+/// Light arriving from infinitely far away, seen by rays that leave the
+/// scene.
+pub trait Environment: DynClone + Debug {
+    /// Radiance arriving from `direction`.
+    fn value(&self, direction: &Vec3) -> Color;
+
+    /// Coverage written for camera rays that escape along `direction`. Zero
+    /// lets whatever the render is composited over show through.
+    fn alpha(&self, _direction: &Vec3) -> f64 {
+        1.0
+    }
+
+    /// Draws a direction to mix into diffuse bounces, with its solid angle
+    /// density. `None` leaves finding the light to the materials.
+    fn sample(&self, _u: (f64, f64)) -> Option<(Vec3, f64)> {
+        None
+    }
+
+    /// Solid angle density of `sample` returning `direction`.
+    fn pdf(&self, _direction: &Vec3) -> f64 {
+        0.0
+    }
+}
+
+dyn_clone::clone_trait_object!(Environment);
+
+/// The same radiance from every direction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Constant {
+    pub color: Color,
+}
+
+impl Environment for Constant {
+    fn value(&self, _direction: &Vec3) -> Color {
+        self.color
+    }
+}
+
+/// Vertical blend from `bottom` straight down to `top` straight up. The
+/// default is the renderer's original white to sky blue.
+#[derive(Clone, Copy, Debug)]
+pub struct Gradient {
+    pub bottom: Color,
+    pub top: Color,
+}
+
+impl Gradient {
+    /// White below, `top` above.
+    pub fn new(top: Color) -> Self {
+        Self {
+            bottom: Color::new(1.0, 1.0, 1.0),
+            top,
+        }
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::new(Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Environment for Gradient {
+    fn value(&self, direction: &Vec3) -> Color {
+        let a = 0.5 * (direction.unit_vector().y + 1.0);
+        self.bottom * (1.0 - a) + self.top * a
+    }
+}
+
+/// Lights the scene with `lighting`, but camera rays that see it get an
+/// alpha of zero, so the render can be composited over a background plate.
+#[derive(Clone, Debug)]
+pub struct Transparent {
+    pub lighting: Box<dyn Environment>,
+}
+
+impl Transparent {
+    pub fn new(lighting: impl Environment + 'static) -> Self {
+        Self {
+            lighting: Box::new(lighting),
+        }
+    }
+}
+
+impl Environment for Transparent {
+    fn value(&self, direction: &Vec3) -> Color {
+        self.lighting.value(direction)
+    }
+
+    fn alpha(&self, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    fn sample(&self, u: (f64, f64)) -> Option<(Vec3, f64)> {
+        self.lighting.sample(u)
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        self.lighting.pdf(direction)
+    }
+}
+
+/// Skybox made of six square images, in the order +X, -X, +Y, -Y, +Z, -Z,
+/// laid out like OpenGL cube map faces.
+#[derive(Clone, Debug)]
+pub struct Cubemap {
+    /// Scale applied to every texel.
+    pub intensity: f64,
+    faces: [Rgb32FImage; 6],
+}
+
+impl Cubemap {
+    pub fn new(faces: [Rgb32FImage; 6]) -> Self {
+        Self {
+            intensity: 1.0,
+            faces,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(paths: [P; 6]) -> ImageResult<Self> {
+        let mut faces = Vec::with_capacity(6);
+        for path in paths {
+            faces.push(image::open(path)?.into_rgb32f());
+        }
+        let faces: [Rgb32FImage; 6] = faces.try_into().expect("six faces");
+        Ok(Self::new(faces))
+    }
+}
+
+impl Environment for Cubemap {
+    fn value(&self, direction: &Vec3) -> Color {
+        let (x, y, z) = (direction.x, direction.y, direction.z);
+        let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+        // Face, then the face coordinates in [-1, 1] with t going down.
+        let (face, s, t) = if ax >= ay && ax >= az {
+            if x > 0.0 {
+                (0, -z / ax, -y / ax)
+            } else {
+                (1, z / ax, -y / ax)
+            }
+        } else if ay >= az {
+            if y > 0.0 {
+                (2, x / ay, z / ay)
+            } else {
+                (3, x / ay, -z / ay)
+            }
+        } else if z > 0.0 {
+            (4, x / az, -y / az)
+        } else {
+            (5, -x / az, -y / az)
+        };
+
+        let image = &self.faces[face];
+        let (width, height) = image.dimensions();
+        let texel = |px: f64, py: f64| {
+            let px = (px as i64).clamp(0, width as i64 - 1) as u32;
+            let py = (py as i64).clamp(0, height as i64 - 1) as u32;
+            color::from_rgb32f(image.get_pixel(px, py))
+        };
+
+        // Bilinear filtering between texel centers.
+        let px = (s + 1.0) * 0.5 * width as f64 - 0.5;
+        let py = (t + 1.0) * 0.5 * height as f64 - 0.5;
+        let (x0, y0) = (px.floor(), py.floor());
+        let (fx, fy) = (px - x0, py - y0);
+        let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1.0, y0) * fx;
+        let bottom = texel(x0, y0 + 1.0) * (1.0 - fx) + texel(x0 + 1.0, y0 + 1.0) * fx;
+        (top * (1.0 - fy) + bottom * fy) * self.intensity
+    }
+}
+
+/// Image based lighting from an equirectangular (latitude-longitude) map.
+///
+/// The center of the image is straight ahead along -Z, its top row is +Y.
+/// Directions can be drawn in proportion to the map's luminance, so small,
+/// very bright features such as a sun are found by diffuse bounces instead of
+/// showing up as fireflies.
+#[derive(Clone, Debug)]
+pub struct EnvironmentMap {
+    /// Rotation around the vertical axis, in degrees.
+    pub rotation: f64,
+    /// Scale applied to every texel.
+    pub intensity: f64,
+    /// Sample directions from the map's luminance instead of leaving it to
+    /// the materials.
+    pub importance_sampling: bool,
+
+    width: u32,
+    height: u32,
+    texels: Vec<Color>,
+    /// Cumulative distribution over rows, then over the texels of each row.
+    rows: Distribution,
+    columns: Vec<Distribution>,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: &Rgb32FImage) -> Self {
+        let (width, height) = image.dimensions();
+        let texels: Vec<Color> = image.pixels().map(color::from_rgb32f).collect();
+
+        // Rows near the poles cover less solid angle, weigh them by sin(theta).
+        let columns: Vec<Distribution> = texels
+            .chunks(width as usize)
+            .enumerate()
+            .map(|(y, row)| {
+                let sin_theta = ((y as f64 + 0.5) / height as f64 * PI).sin();
+                Distribution::new(
+                    row.iter()
+                        .map(|&c| color::luminance(c).max(0.0) * sin_theta),
+                )
+            })
+            .collect();
+        let rows = Distribution::new(columns.iter().map(|row| row.total));
+
+        Self {
+            rotation: 0.0,
+            intensity: 1.0,
+            importance_sampling: true,
+            width,
+            height,
+            texels,
+            rows,
+            columns,
+        }
+    }
+
+    /// Loads an `.hdr` or `.exr` (or any other format `image` can read).
+    pub fn load(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Self::new(&image::open(path)?.into_rgb32f()))
+    }
+
+    fn texel_pdf(&self, x: u32, y: u32, theta: f64) -> f64 {
+        let sin_theta = theta.sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let row = &self.columns[y as usize];
+        let probability = self.rows.probability(y as usize) * row.probability(x as usize);
+        // Texel probability to density over the unit square of the map, then
+        // over solid angle.
+        probability * (self.width * self.height) as f64 / (2.0 * PI * PI * sin_theta)
+    }
+
+    /// `direction` in the map's own (unrotated) frame, normalized.
+    fn to_map(&self, direction: &Vec3) -> Vec3 {
+        rotate_y(direction, -self.rotation.to_radians()).unit_vector()
+    }
+
+    fn texel(&self, local: &Vec3) -> (u32, u32) {
+        let u = 0.5 + local.x.atan2(-local.z) / (2.0 * PI);
+        let v = local.y.clamp(-1.0, 1.0).acos() / PI;
+        let x = ((u * self.width as f64) as u32).min(self.width - 1);
+        let y = ((v * self.height as f64) as u32).min(self.height - 1);
+        (x, y)
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn value(&self, direction: &Vec3) -> Color {
+        let (x, y) = self.texel(&self.to_map(direction));
+        self.texels[(y * self.width + x) as usize] * self.intensity
+    }
+
+    /// Draws a direction with probability proportional to the luminance
+    /// seen along it.
+    fn sample(&self, (u1, u2): (f64, f64)) -> Option<(Vec3, f64)> {
+        if !self.importance_sampling {
+            return None;
+        }
+        let (y, v) = self.rows.sample(u2);
+        let (x, u) = self.columns[y].sample(u1);
+
+        let phi = (u - 0.5) * 2.0 * PI;
+        let theta = v * PI;
+        let local = Vec3::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        );
+        let direction = rotate_y(&local, self.rotation.to_radians());
+        Some((direction, self.texel_pdf(x as u32, y as u32, theta)))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        let local = self.to_map(direction);
+        let (x, y) = self.texel(&local);
+        let theta = local.y.clamp(-1.0, 1.0).acos();
+        self.texel_pdf(x, y, theta)
+    }
+}
+
+fn rotate_y(v: &Vec3, angle: f64) -> Vec3 {
+    let (sin, cos) = angle.sin_cos();
+    Vec3::new(cos * v.x + sin * v.z, v.y, -sin * v.x + cos * v.z)
+}
+
+/// Piecewise constant distribution over equal width cells of [0, 1).
+#[derive(Clone, Debug)]
+struct Distribution {
+    cdf: Vec<f64>,
+    total: f64,
+}
+
+impl Distribution {
+    fn new(weights: impl Iterator<Item = f64>) -> Self {
+        let mut cdf = vec![0.0];
+        for weight in weights {
+            cdf.push(cdf[cdf.len() - 1] + weight);
+        }
+        let total = cdf[cdf.len() - 1];
+        let cells = (cdf.len() - 1) as f64;
+        for (i, value) in cdf.iter_mut().enumerate() {
+            // An all black row or map falls back to uniform.
+            *value = if total > 0.0 {
+                *value / total
+            } else {
+                i as f64 / cells
+            };
+        }
+        Self { cdf, total }
+    }
+
+    fn probability(&self, cell: usize) -> f64 {
+        self.cdf[cell + 1] - self.cdf[cell]
+    }
+
+    /// Picks a cell for `u` in [0, 1) and returns it with a continuous
+    /// position in [0, 1) that falls inside it.
+    fn sample(&self, u: f64) -> (usize, f64) {
+        let cells = self.cdf.len() - 1;
+        let cell = (self.cdf.partition_point(|&c| c <= u).max(1) - 1).min(cells - 1);
+        let probability = self.probability(cell);
+        let offset = if probability > 0.0 {
+            ((u - self.cdf[cell]) / probability).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        (cell, (cell as f64 + offset) / cells as f64)
+    }
+}
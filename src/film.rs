@@ -0,0 +1,122 @@
+use image::{Rgba, Rgba32FImage};
+
+use crate::{
+    color::{self, Color},
+    filter::Filter,
+};
+
+/// Accumulates filtered camera samples. Every pixel keeps the weighted sum
+/// of the samples (color and alpha) that landed near it and the sum of their
+/// weights; the image is their ratio.
+pub struct Film {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    filter: Filter,
+    pub(crate) sum: Vec<Color>,
+    pub(crate) alpha: Vec<f64>,
+    pub(crate) weight: Vec<f64>,
+    pub(crate) shadow: Vec<Shadow>,
+}
+
+/// Weighted sums over the samples of a pixel that hit a shadow catcher: the
+/// luminance of the light they received, and of the light they would have
+/// received without the rest of the scene in the way.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Shadow {
+    pub(crate) weight: f64,
+    pub(crate) lit: f64,
+    pub(crate) unshadowed: f64,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32, filter: Filter) -> Self {
+        let len = (width * height) as usize;
+        Self {
+            width,
+            height,
+            filter,
+            sum: vec![Color::default(); len],
+            alpha: vec![0.0; len],
+            weight: vec![0.0; len],
+            shadow: vec![Shadow::default(); len],
+        }
+    }
+
+    /// Splats a sample taken at `offset` (in pixels, relative to the center
+    /// of pixel (x, y)) into all pixels within the filter radius. `color` is
+    /// premultiplied by `alpha`.
+    pub fn add_sample(&mut self, x: u32, y: u32, offset: (f64, f64), color: Color, alpha: f64) {
+        self.splat(x, y, offset, |film, index, weight| {
+            film.sum[index] += color * weight;
+            film.alpha[index] += alpha * weight;
+        });
+    }
+
+    /// Splats a sample that hit a shadow catcher, given the luminance of the
+    /// light it received with and without the rest of the scene.
+    pub fn add_shadow(&mut self, x: u32, y: u32, offset: (f64, f64), lit: f64, unshadowed: f64) {
+        self.splat(x, y, offset, |film, index, weight| {
+            film.alpha[index] += weight;
+            let shadow = &mut film.shadow[index];
+            shadow.weight += weight;
+            shadow.lit += lit * weight;
+            shadow.unshadowed += unshadowed * weight;
+        });
+    }
+
+    fn splat(
+        &mut self,
+        x: u32,
+        y: u32,
+        offset: (f64, f64),
+        mut add: impl FnMut(&mut Self, usize, f64),
+    ) {
+        let radius = self.filter.radius();
+        let sample_x = x as f64 + offset.0;
+        let sample_y = y as f64 + offset.1;
+
+        let x0 = (sample_x - radius).ceil().max(0.0) as u32;
+        let x1 = ((sample_x + radius).floor() as i64).min(self.width as i64 - 1);
+        let y0 = (sample_y - radius).ceil().max(0.0) as u32;
+        let y1 = ((sample_y + radius).floor() as i64).min(self.height as i64 - 1);
+
+        for py in y0 as i64..=y1 {
+            for px in x0 as i64..=x1 {
+                let weight = self
+                    .filter
+                    .evaluate(px as f64 - sample_x, py as f64 - sample_y);
+                if weight != 0.0 {
+                    let index = py as usize * self.width as usize + px as usize;
+                    add(self, index, weight);
+                    self.weight[index] += weight;
+                }
+            }
+        }
+    }
+
+    /// Premultiplied RGBA. Shadow catcher samples start out fully covered;
+    /// the share of their light that reached them uncovers them again, so
+    /// only the shadow is left in the alpha channel.
+    ///
+    /// Filters with negative lobes can leave a pixel with no net weight, or
+    /// ring below zero next to bright samples. Such pixels stay empty, and
+    /// the ringing is clipped at zero.
+    pub fn to_image(&self) -> Rgba32FImage {
+        Rgba32FImage::from_fn(self.width, self.height, |x, y| {
+            let index = (y * self.width + x) as usize;
+            let weight = self.weight[index];
+            if weight <= 1e-12 {
+                return Rgba([0.0; 4]);
+            }
+            let shadow = self.shadow[index];
+            let visible = if shadow.unshadowed > 0.0 {
+                (shadow.lit / shadow.unshadowed).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            let alpha = (self.alpha[index] - shadow.weight * visible) / weight;
+            let [r, g, b] = color::color_rgb32f(self.sum[index] / weight).map(|c| c.max(0.0));
+            Rgba([r, g, b, alpha.clamp(0.0, 1.0) as f32])
+        })
+    }
+}
@@ -1,30 +1,43 @@
 use crate::interval::Interval;
 use crate::material::{Lambertian, Material};
-use crate::{Vec3, Point3};
-use crate::Ray;
+use crate::ray::{Point3, Ray};
+use crate::vec3::Vec3;
 
 #[derive(Clone)]
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
+    /// Index of the hit object in the outermost `HittableList`.
+    pub object_id: u32,
     pub mat: Box<dyn Material>
 }
 
+impl Default for HitRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HitRecord {
     pub fn new() -> Self {
         HitRecord {
             p: Point3::new(0.0, 0.0, 0.0),
             normal: Vec3::new(0.0, 0.0, 0.0),
             t: 0.0,
+            u: 0.0,
+            v: 0.0,
             front_face: false,
+            object_id: 0,
             mat: Box::new(Lambertian::default())
         }
     }
 
     pub fn set_face_normal(&mut self, r: &Ray, outward_normal: &Vec3) {
-        self.front_face = r.direction.dot(&outward_normal) < 0.0;
+        self.front_face = r.direction.dot(outward_normal) < 0.0;
         self.normal = if self.front_face { *outward_normal } else { -*outward_normal };
     }
 }
@@ -4,16 +4,11 @@ use crate::{
     ray::Ray,
 };
 
+#[derive(Default)]
 pub struct HittableList {
     pub objects: Vec<Box<dyn Hittable>>,
 }
 
-impl Default for HittableList {
-    fn default() -> Self {
-        Self { objects: Vec::new() }
-    }
-}
-
 impl HittableList {
     pub fn add<T: Hittable + 'static>(&mut self, object: T) { // assuming we never deallocate
         self.objects.push(Box::new(object));
@@ -25,12 +20,13 @@ impl Hittable for HittableList {
         let mut closest_so_far = ray_t.max;
         let mut result: Option<HitRecord> = None;
 
-        for object in self.objects.iter() {
-            if let Some(hit) = object.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
+        for (index, object) in self.objects.iter().enumerate() {
+            if let Some(mut hit) = object.hit(r, &Interval::new(ray_t.min, closest_so_far)) {
                 closest_so_far = hit.t;
+                hit.object_id = index as u32;
                 result = Some(hit);
             }
         }
-        return result;
+        result
     }
 }
@@ -5,7 +5,7 @@ pub struct Interval {
 
 impl Interval {
     pub fn new(min: f64, max: f64) -> Self {
-        Interval { min: min, max: max }
+        Interval { min, max }
     }
 
     pub fn size(&self) -> f64 {
@@ -31,5 +31,5 @@ impl Interval {
     }
 }
 
-pub const UNIVERSE: Interval = Interval { min: -std::f64::INFINITY, max: std::f64::INFINITY };
-pub const EMPTY: Interval = Interval { min: std::f64::INFINITY, max: -std::f64::INFINITY };
+pub const UNIVERSE: Interval = Interval { min: -f64::INFINITY, max: f64::INFINITY };
+pub const EMPTY: Interval = Interval { min: f64::INFINITY, max: -f64::INFINITY };
@@ -0,0 +1,538 @@
+use std::{f64::consts::PI, io, sync::Arc};
+
+use image::GrayImage;
+
+use crate::{ray::Ray, vec3::Vec3};
+
+/// One surface of a lens system, as a row of a lens description table.
+/// Tables list the surfaces from the front of the lens to the back, in
+/// millimeters, the same layout as the lens files PBRT reads.
+#[derive(Clone, Copy, Debug)]
+pub struct LensElement {
+    /// Signed radius of curvature, positive when the surface bulges towards
+    /// the scene. Zero marks the aperture stop.
+    pub radius: f64,
+    /// Distance along the axis to the next surface. For the last surface
+    /// this is the distance to the film, which focusing replaces.
+    pub thickness: f64,
+    /// Index of refraction behind the surface; 0 or 1 for air.
+    pub eta: f64,
+    /// Diameter of the surface's clear aperture.
+    pub aperture: f64,
+}
+
+impl LensElement {
+    /// Parses a table with one surface per line (radius, thickness, index
+    /// and aperture diameter, separated by whitespace). Blank lines and
+    /// lines starting with `#` are skipped.
+    pub fn parse_table(table: &str) -> io::Result<Vec<LensElement>> {
+        let mut elements = Vec::new();
+        for (number, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected four numbers", number + 1),
+                )
+            };
+            let values = line
+                .split_whitespace()
+                .map(|v| v.parse::<f64>().map_err(|_| invalid()))
+                .collect::<io::Result<Vec<f64>>>()?;
+            let [radius, thickness, eta, aperture] = values[..] else {
+                return Err(invalid());
+            };
+            elements.push(LensElement {
+                radius,
+                thickness,
+                eta,
+                aperture,
+            });
+        }
+        Ok(elements)
+    }
+}
+
+/// A 50 mm f/2 double-Gauss design (US patent 2,673,491, scaled from the
+/// version in Smith's "Modern Lens Design").
+pub const DOUBLE_GAUSS_50MM: [LensElement; 11] = [
+    element(29.475, 3.76, 1.67, 25.2),
+    element(84.83, 0.12, 1.0, 25.2),
+    element(19.275, 4.025, 1.67, 23.0),
+    element(40.77, 3.275, 1.699, 23.0),
+    element(12.75, 5.705, 1.0, 18.0),
+    element(0.0, 4.5, 0.0, 17.1),
+    element(-14.495, 1.18, 1.603, 17.0),
+    element(40.77, 6.065, 1.658, 20.0),
+    element(-20.385, 0.19, 1.0, 20.0),
+    element(437.065, 3.22, 1.717, 20.0),
+    element(-39.73, 0.0, 1.0, 20.0),
+];
+
+const fn element(radius: f64, thickness: f64, eta: f64, aperture: f64) -> LensElement {
+    LensElement {
+        radius,
+        thickness,
+        eta,
+        aperture,
+    }
+}
+
+/// Shape of the opening in the aperture stop, which out of focus
+/// highlights (bokeh) take on.
+#[derive(Clone, Debug, Default)]
+pub enum ApertureShape {
+    #[default]
+    Circle,
+    /// Regular polygon inscribed in the stop, like an iris with `blades`
+    /// straight blades, turned by `rotation` degrees.
+    Polygon { blades: u32, rotation: f64 },
+    /// Mask stretched over the square around the stop: white lets light
+    /// through, black blocks it.
+    Image(Arc<GrayImage>),
+}
+
+impl ApertureShape {
+    /// Share of the light let through at `(x, y)`, in units of the stop's
+    /// radius.
+    fn transmission(&self, x: f64, y: f64) -> f64 {
+        let r2 = x * x + y * y;
+        if r2 > 1.0 {
+            return 0.0;
+        }
+        match self {
+            ApertureShape::Circle => 1.0,
+            ApertureShape::Polygon { blades, rotation } => {
+                let blades = (*blades).max(3) as f64;
+                let sector = 2.0 * PI / blades;
+                let angle = (y.atan2(x) - rotation.to_radians()).rem_euclid(sector);
+                let distance = r2.sqrt() * (angle - sector / 2.0).cos();
+                if distance <= (sector / 2.0).cos() {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ApertureShape::Image(mask) => {
+                let (width, height) = mask.dimensions();
+                let px = ((x + 1.0) / 2.0 * width as f64) as u32;
+                let py = ((1.0 - y) / 2.0 * height as f64) as u32;
+                mask.get_pixel(px.min(width - 1), py.min(height - 1)).0[0] as f64 / 255.0
+            }
+        }
+    }
+}
+
+/// Camera lens made of spherical elements, traced surface by surface, so
+/// it shows the vignetting, field curvature, distortion and bokeh of the
+/// real design. Set on a camera with `CameraBuilder::lens`, where it
+/// replaces the projection.
+///
+/// The lens table is in millimeters and the scene in meters. The film sits
+/// at the camera center, with the lens in front of it.
+#[derive(Clone, Debug)]
+pub struct RealisticLens {
+    pub elements: Vec<LensElement>,
+    /// Distance from the film to the plane in focus, in meters.
+    pub focus_distance: f64,
+    /// Diagonal of the film, in millimeters. The default is full frame
+    /// 35 mm.
+    pub film_diagonal: f64,
+    /// Diameter of the opening in the aperture stop, in millimeters. `None`
+    /// keeps the table's, which is usually wide open.
+    pub aperture_diameter: Option<f64>,
+    pub aperture_shape: ApertureShape,
+}
+
+impl Default for RealisticLens {
+    fn default() -> Self {
+        Self {
+            elements: DOUBLE_GAUSS_50MM.to_vec(),
+            focus_distance: 10.0,
+            film_diagonal: 43.27,
+            aperture_diameter: None,
+            aperture_shape: ApertureShape::default(),
+        }
+    }
+}
+
+/// Film radii the exit pupil is bounded for.
+const PUPIL_BINS: usize = 16;
+
+/// A `RealisticLens` converted to meters, focused, and with the bounds of
+/// its exit pupil precomputed.
+#[derive(Clone, Debug)]
+pub(crate) struct LensSystem {
+    elements: Vec<LensElement>,
+    film_diagonal: f64,
+    shape: ApertureShape,
+    /// For film points along +X, a box on the rear element that holds every
+    /// direction light can leave the lens through, indexed by film radius.
+    pupil: Vec<Bounds>,
+    /// Brings the center of the image to the scene's radiance.
+    scale: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    min: (f64, f64),
+    max: (f64, f64),
+}
+
+impl Bounds {
+    fn area(&self) -> f64 {
+        (self.max.0 - self.min.0).max(0.0) * (self.max.1 - self.min.1).max(0.0)
+    }
+
+    fn lerp(&self, (u1, u2): (f64, f64)) -> (f64, f64) {
+        (
+            self.min.0 + u1 * (self.max.0 - self.min.0),
+            self.min.1 + u2 * (self.max.1 - self.min.1),
+        )
+    }
+}
+
+impl RealisticLens {
+    /// Effective focal length in millimeters, or `None` if light parallel to
+    /// the axis doesn't make it through the lens.
+    pub fn focal_length(&self) -> Option<f64> {
+        LensSystem::unfocused(self)
+            .focal_length()
+            .map(|f| f * 1000.0)
+    }
+}
+
+impl LensSystem {
+    pub(crate) fn new(lens: &RealisticLens) -> Self {
+        let mut system = Self::unfocused(lens);
+        system.focus(lens.focus_distance);
+        system.bound_exit_pupil();
+        system
+    }
+
+    /// The lens in meters, as the table places it.
+    fn unfocused(lens: &RealisticLens) -> Self {
+        let mm = |v: f64| v * 0.001;
+        let mut elements: Vec<LensElement> = lens
+            .elements
+            .iter()
+            .map(|e| LensElement {
+                radius: mm(e.radius),
+                thickness: mm(e.thickness),
+                eta: e.eta,
+                aperture: mm(e.aperture),
+            })
+            .collect();
+        if let Some(diameter) = lens.aperture_diameter {
+            for e in elements.iter_mut().filter(|e| e.radius == 0.0) {
+                e.aperture = mm(diameter);
+            }
+        }
+
+        Self {
+            elements,
+            film_diagonal: mm(lens.film_diagonal),
+            shape: lens.aperture_shape.clone(),
+            pupil: Vec::new(),
+            scale: 1.0,
+        }
+    }
+
+    fn rear_z(&self) -> f64 {
+        -self.elements.last().map_or(0.0, |e| e.thickness)
+    }
+
+    fn front_z(&self) -> f64 {
+        -self.elements.iter().map(|e| e.thickness).sum::<f64>()
+    }
+
+    fn rear_radius(&self) -> f64 {
+        self.elements.last().map_or(0.0, |e| e.aperture / 2.0)
+    }
+
+    /// Traces a ray leaving the film (towards -Z) out through the lens.
+    /// Returns the ray in the scene and the share of light the aperture
+    /// shape lets through, or `None` if the lens blocks it.
+    fn trace_from_film(&self, ray: &Ray, shape: &ApertureShape) -> Option<(Ray, f64)> {
+        let (mut origin, mut direction) = (ray.origin, ray.direction);
+        let mut transmission = 1.0;
+        let mut z = 0.0;
+        for (i, element) in self.elements.iter().enumerate().rev() {
+            z -= element.thickness;
+            let (p, normal) = self.cross(element, z, origin, direction)?;
+            origin = p;
+            match normal {
+                Some(normal) => {
+                    let eta_front = match i {
+                        0 => 1.0,
+                        _ => air_if_zero(self.elements[i - 1].eta),
+                    };
+                    direction = refract(direction, normal, air_if_zero(element.eta) / eta_front)?;
+                }
+                None => {
+                    let radius = element.aperture / 2.0;
+                    transmission *= shape.transmission(p.x / radius, p.y / radius);
+                    if transmission <= 0.0 {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some((Ray::new(origin, direction), transmission))
+    }
+
+    /// Traces a ray coming from the scene (towards +Z) in through the lens.
+    fn trace_from_scene(&self, ray: &Ray) -> Option<Ray> {
+        let (mut origin, mut direction) = (ray.origin, ray.direction);
+        let mut z = self.front_z();
+        for (i, element) in self.elements.iter().enumerate() {
+            let (p, normal) = self.cross(element, z, origin, direction)?;
+            origin = p;
+            if let Some(normal) = normal {
+                let eta_front = match i {
+                    0 => 1.0,
+                    _ => air_if_zero(self.elements[i - 1].eta),
+                };
+                direction = refract(direction, normal, eta_front / air_if_zero(element.eta))?;
+            }
+            z += element.thickness;
+        }
+        Some(Ray::new(origin, direction))
+    }
+
+    /// Where a ray crosses `element`, whose vertex is at `z`, with the
+    /// surface normal facing the ray (none for the stop). `None` if it
+    /// misses the element's clear aperture.
+    fn cross(
+        &self,
+        element: &LensElement,
+        z: f64,
+        origin: Vec3,
+        direction: Vec3,
+    ) -> Option<(Vec3, Option<Vec3>)> {
+        let (t, normal) = if element.radius == 0.0 {
+            ((z - origin.z) / direction.z, None)
+        } else {
+            let (t, normal) =
+                intersect_surface(element.radius, z + element.radius, origin, direction)?;
+            (t, Some(normal))
+        };
+        let p = origin + direction * t;
+        let radius = element.aperture / 2.0;
+        if t < 0.0 || p.x * p.x + p.y * p.y > radius * radius {
+            return None;
+        }
+        Some((p, normal))
+    }
+
+    /// Moves the lens so that the plane `distance` in front of the film is
+    /// sharp, using the thick lens approximation of the system.
+    fn focus(&mut self, distance: f64) {
+        // Rays parallel to the axis, close to it, from either side.
+        let height = 0.001 * self.film_diagonal;
+        let from_scene = Ray::new(
+            Vec3::new(height, 0.0, self.front_z() - 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        let from_film = Ray::new(
+            Vec3::new(height, 0.0, self.rear_z() + 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        let Some(image_side) = self.trace_from_scene(&from_scene) else {
+            return;
+        };
+        let Some((scene_side, _)) = self.trace_from_film(&from_film, &ApertureShape::Circle) else {
+            return;
+        };
+
+        // Principal planes (where the bent ray meets the incoming height)
+        // and the focal length.
+        let at_height = |ray: &Ray, x: f64| ray.at((x - ray.origin.x) / ray.direction.x).z;
+        let image_principal = at_height(&image_side, height);
+        let image_focal = at_height(&image_side, 0.0);
+        let scene_principal = at_height(&scene_side, height);
+        let focal_length = image_focal - image_principal;
+
+        // Moving the lens by delta towards the scene has to satisfy
+        // 1 / (a - delta) + 1 / (b + delta) = 1 / f.
+        let a = scene_principal + distance;
+        let b = -image_principal;
+        let c = ((a + b) * (a + b - 4.0 * focal_length)).max(0.0);
+        let delta = 0.5 * (a - b - c.sqrt());
+        if let Some(last) = self.elements.last_mut() {
+            last.thickness += delta;
+        }
+    }
+
+    /// Finds, for a range of film radii, the part of the rear element that
+    /// light from the scene can pass through, by tracing a grid of rays.
+    fn bound_exit_pupil(&mut self) {
+        const GRID: usize = 32;
+        const FILM_SAMPLES: usize = 4;
+        let extent = 1.5 * self.rear_radius();
+        let cell = 2.0 * extent / GRID as f64;
+        let rear_z = self.rear_z();
+        let half_diagonal = self.film_diagonal / 2.0;
+
+        self.pupil = (0..PUPIL_BINS)
+            .map(|bin| {
+                let mut bounds = Bounds {
+                    min: (f64::INFINITY, f64::INFINITY),
+                    max: (f64::NEG_INFINITY, f64::NEG_INFINITY),
+                };
+                for k in 0..FILM_SAMPLES {
+                    let r = (bin as f64 + k as f64 / (FILM_SAMPLES - 1) as f64) / PUPIL_BINS as f64
+                        * half_diagonal;
+                    for gy in 0..=GRID {
+                        for gx in 0..=GRID {
+                            let x = -extent + gx as f64 * cell;
+                            let y = -extent + gy as f64 * cell;
+                            let film = Vec3::new(r, 0.0, 0.0);
+                            let ray = Ray::new(film, Vec3::new(x, y, rear_z) - film);
+                            if self.trace_from_film(&ray, &ApertureShape::Circle).is_some() {
+                                bounds.min = (bounds.min.0.min(x), bounds.min.1.min(y));
+                                bounds.max = (bounds.max.0.max(x), bounds.max.1.max(y));
+                            }
+                        }
+                    }
+                }
+                // The grid can miss a sliver of the pupil along each edge.
+                bounds.min = (bounds.min.0 - cell, bounds.min.1 - cell);
+                bounds.max = (bounds.max.0 + cell, bounds.max.1 + cell);
+                bounds
+            })
+            .collect();
+
+        // Average weight of the rays from the film center, which should
+        // come out as one.
+        let center = self.pupil[0];
+        let mut total = 0.0;
+        for gy in 0..GRID {
+            for gx in 0..GRID {
+                let u = (
+                    (gx as f64 + 0.5) / GRID as f64,
+                    (gy as f64 + 0.5) / GRID as f64,
+                );
+                let (x, y) = center.lerp(u);
+                let direction = Vec3::new(x, y, rear_z).unit_vector();
+                let ray = Ray::new(Vec3::default(), direction);
+                if let Some((_, transmission)) = self.trace_from_film(&ray, &self.shape) {
+                    total += direction.z.powi(4) * transmission;
+                }
+            }
+        }
+        self.scale = if total > 0.0 {
+            (GRID * GRID) as f64 / total
+        } else {
+            1.0
+        };
+    }
+
+    /// Ray for the image position `(s, t)` (in [0, 1] from the top left) of
+    /// an image `aspect_ratio` times wider than tall, through the point of
+    /// the exit pupil picked by `u`. Returns the ray in camera space with its
+    /// weight, or `None` if the lens blocks it.
+    pub(crate) fn ray(
+        &self,
+        (s, t): (f64, f64),
+        aspect_ratio: f64,
+        u: (f64, f64),
+    ) -> Option<(Vec3, Vec3, f64)> {
+        let height = self.film_diagonal / (1.0 + aspect_ratio * aspect_ratio).sqrt();
+        let width = height * aspect_ratio;
+        // The lens flips the image, so the top left of the picture lands on
+        // the bottom right of the film.
+        let film = Vec3::new((0.5 - s) * width, (t - 0.5) * height, 0.0);
+
+        let r = film.x.hypot(film.y);
+        let bin =
+            ((r / (self.film_diagonal / 2.0) * PUPIL_BINS as f64) as usize).min(PUPIL_BINS - 1);
+        let bounds = self.pupil[bin];
+        if bounds.area() <= 0.0 {
+            return None;
+        }
+        let (px, py) = bounds.lerp(u);
+        let (sin, cos) = if r > 0.0 {
+            (film.y / r, film.x / r)
+        } else {
+            (0.0, 1.0)
+        };
+        let rear = Vec3::new(cos * px - sin * py, sin * px + cos * py, self.rear_z());
+
+        let direction = (rear - film).unit_vector();
+        let (ray, transmission) = self.trace_from_film(&Ray::new(film, direction), &self.shape)?;
+        let cos4 = direction.z.powi(4);
+        let weight = cos4 * transmission * bounds.area() / self.pupil[0].area() * self.scale;
+        Some((ray.origin, ray.direction, weight))
+    }
+
+    /// Effective focal length, from a ray parallel to the axis traced in
+    /// from the scene. In meters.
+    fn focal_length(&self) -> Option<f64> {
+        let height = 0.001 * self.film_diagonal;
+        let ray = Ray::new(
+            Vec3::new(height, 0.0, self.front_z() - 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        );
+        let out = self.trace_from_scene(&ray)?;
+        Some(-height * out.direction.z / out.direction.x)
+    }
+}
+
+/// Intersection of a ray with the spherical surface of radius `radius`
+/// centered on the axis at `center_z`, on the side of the sphere that forms
+/// the lens surface. Returns the distance and the normal facing the ray.
+fn intersect_surface(
+    radius: f64,
+    center_z: f64,
+    origin: Vec3,
+    direction: Vec3,
+) -> Option<(f64, Vec3)> {
+    let o = origin - Vec3::new(0.0, 0.0, center_z);
+    let a = direction.length_squared();
+    let b = 2.0 * direction.dot(&o);
+    let c = o.length_squared() - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let (t0, t1) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+    let closer = (direction.z > 0.0) ^ (radius < 0.0);
+    let t = if closer { t0.min(t1) } else { t0.max(t1) };
+    if t < 0.0 {
+        return None;
+    }
+    let normal = (o + direction * t).unit_vector();
+    let normal = if normal.dot(&direction) > 0.0 {
+        -normal
+    } else {
+        normal
+    };
+    Some((t, normal))
+}
+
+/// Bends `direction` through a surface with `normal` facing it, going from
+/// index `eta` times the one on the other side. `None` on total internal
+/// reflection.
+fn refract(direction: Vec3, normal: Vec3, eta: f64) -> Option<Vec3> {
+    let d = direction.unit_vector();
+    let cos_i = -d.dot(&normal);
+    let sin2_t = eta * eta * (1.0 - cos_i * cos_i).max(0.0);
+    if sin2_t >= 1.0 {
+        return None;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    Some(d * eta + normal * (eta * cos_i - cos_t))
+}
+
+fn air_if_zero(eta: f64) -> f64 {
+    if eta == 0.0 {
+        1.0
+    } else {
+        eta
+    }
+}
@@ -0,0 +1,31 @@
+pub mod animation;
+pub mod aov;
+pub mod camera;
+pub mod color;
+pub mod compare;
+pub mod denoise;
+pub mod environment;
+pub mod film;
+pub mod filter;
+pub mod hittable;
+pub mod hittable_list;
+pub mod interval;
+pub mod lens;
+pub mod material;
+pub mod onb;
+pub mod output;
+pub mod projection;
+pub mod quad;
+pub mod ray;
+pub mod spectrum;
+pub mod sampler;
+pub mod scene;
+pub mod sky;
+pub mod sphere;
+pub mod texture;
+pub mod tonemap;
+pub mod transform;
+pub mod util;
+pub mod vec3;
+
+mod checkpoint;
@@ -1,23 +1,92 @@
 use dyn_clone::DynClone;
 
-use crate::{color::Color, hittable::HitRecord, ray::Ray, util::random_f64, vec3::Vec3};
+use crate::{
+    color::{self, Color},
+    hittable::HitRecord,
+    onb::Onb,
+    ray::Ray,
+    sampler::Sampler,
+    spectrum,
+    texture::{SolidColor, Texture},
+    vec3::Vec3,
+};
 
 pub trait Material: DynClone {
     // TODO: make it so the Ray have a certain probability to scatter, not to always scatter with some attenuation
     // i feel like this will look nicer
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<(Ray, Color)>;
+    /// Every random decision is drawn from `sampler`, which the camera has
+    /// already positioned at the dimensions reserved for this bounce.
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)>;
+
+    /// Surface color at the hit, without lighting. Written to the albedo AOV
+    /// and used to guide the denoiser.
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    /// Solid angle density with which `scatter` picks `scattered`, for
+    /// materials whose attenuation equals the BRDF times the cosine over
+    /// that density. The camera uses it to mix in light sampling. Zero (the
+    /// default) means the material has to be sampled on its own.
+    fn scattering_pdf(&self, _ray: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    /// How the camera treats this material where it sees it directly, for
+    /// compositing. `None` (the default) renders it like any other.
+    fn matte(&self) -> Option<Matte> {
+        None
+    }
+
+    /// Light the surface gives off at the hit, back along the ray. Black
+    /// (the default) for everything but lights.
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    /// Like `scatter`, for light a layer on top (such as a thin film) has
+    /// already let through the surface's interface, so the material must
+    /// not reflect off it again. The default scatters as usual.
+    fn transmit(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        self.scatter(ray, rec, sampler)
+    }
 }
 
 dyn_clone::clone_trait_object!(Material);
 
+/// Compositing role of a material, see `Material::matte`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Matte {
+    /// Cuts a hole: black with zero alpha.
+    Holdout,
+    /// Invisible except for the shadows other objects cast on it, which
+    /// are written as black with the amount of shadow in the alpha channel.
+    ShadowCatcher,
+}
+
 #[derive(Clone, Copy, Default)]
 pub struct Lambertian {
     pub albedo: Color,
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
-        let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
+    fn scatter(
+        &self,
+        _ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let mut scatter_direction = rec.normal + Vec3::sample_unit_vector(sampler.get_2d());
 
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal;
@@ -27,6 +96,15 @@ impl Material for Lambertian {
         let attenuation = self.albedo;
         Some((scattered, attenuation))
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    fn scattering_pdf(&self, _ray: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cosine = rec.normal.dot(&scattered.direction.unit_vector());
+        cosine.max(0.0) / std::f64::consts::PI
+    }
 }
 
 #[derive(Clone, Copy, Default)]
@@ -36,9 +114,14 @@ pub struct Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
         let reflected = Vec3::reflect(&ray.direction, &rec.normal).unit_vector()
-            + Vec3::random_unit_vector() * self.fuzz;
+            + Vec3::sample_unit_vector(sampler.get_2d()) * self.fuzz;
         let scattered = Ray::new(rec.p, reflected);
         let attenuation = self.albedo;
         if scattered.direction.dot(&rec.normal) > 0.0 {
@@ -47,27 +130,145 @@ impl Material for Metal {
             None
         }
     }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.albedo
+    }
+}
+
+/// Wavelength dependence of a dielectric's index of refraction. Wavelengths
+/// are in nanometers; the models take micrometers like published tables.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Dispersion {
+    #[default]
+    None,
+    /// `n = a + b / λ²`
+    Cauchy { a: f64, b: f64 },
+    /// `n² = 1 + Σ bᵢλ² / (λ² - cᵢ)`
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Schott N-BK7 crown glass.
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+        c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+    };
+
+    /// Natural diamond.
+    pub const DIAMOND: Dispersion = Dispersion::Sellmeier {
+        b: [0.3306, 4.3356, 0.0],
+        c: [0.030_625, 0.011_236, 0.0],
+    };
+
+    /// Fraunhofer d line, the reference wavelength for quoted IORs.
+    pub const D_LINE: f64 = 587.6;
+
+    pub fn index_at(&self, wavelength: f64) -> Option<f64> {
+        let l2 = (wavelength * 1e-3).powi(2);
+        match *self {
+            Dispersion::None => None,
+            Dispersion::Cauchy { a, b } => Some(a + b / l2),
+            Dispersion::Sellmeier { b, c } => {
+                let sum: f64 = (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum();
+                Some((1.0 + sum).sqrt())
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default)]
 pub struct Dielectric {
+    /// Index of refraction used for RGB rays, and for spectral rays when
+    /// there is no dispersion model.
     pub refraction_index: f64,
+    pub dispersion: Dispersion,
+    /// Beer-Lambert absorption coefficient per unit of distance traveled
+    /// inside the medium. Zero is perfectly clear glass.
+    pub absorption: Color,
 }
 
 impl Dielectric {
+    pub fn new(refraction_index: f64) -> Self {
+        Self {
+            refraction_index,
+            ..Default::default()
+        }
+    }
+
+    /// Dispersive dielectric. The RGB index is taken at the d line.
+    pub fn dispersive(dispersion: Dispersion) -> Self {
+        Self {
+            refraction_index: dispersion.index_at(Dispersion::D_LINE).unwrap_or(1.5),
+            dispersion,
+            ..Default::default()
+        }
+    }
+
+    fn refraction_index_at(&self, wavelength: Option<f64>) -> f64 {
+        wavelength
+            .and_then(|l| self.dispersion.index_at(l))
+            .unwrap_or(self.refraction_index)
+    }
+
+    /// Tinted dielectric that transmits `color` after light has traveled
+    /// `distance` units through it. Channels above 1 are clamped, since glass
+    /// can't add light. Panics unless `distance` is positive.
+    pub fn tinted(refraction_index: f64, color: Color, distance: f64) -> Self {
+        assert!(
+            distance > 0.0,
+            "tint distance must be positive, got {distance}"
+        );
+        let coefficient = |c: f64| -c.clamp(1e-6, 1.0).ln() / distance;
+        Self {
+            refraction_index,
+            dispersion: Dispersion::None,
+            absorption: Color::new(
+                coefficient(color.x),
+                coefficient(color.y),
+                coefficient(color.z),
+            ),
+        }
+    }
+
+    /// Transmittance along a segment of `distance` units inside the medium.
+    fn transmittance(&self, distance: f64) -> Color {
+        Color::new(
+            (-self.absorption.x * distance).exp(),
+            (-self.absorption.y * distance).exp(),
+            (-self.absorption.z * distance).exp(),
+        )
+    }
+
     fn reflectance(&self, cosine: f64, ref_idx: f64) -> f64 {
         let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
         r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
     }
 }
 
-impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
-        let attenuation = Color::new(1.0, 1.0, 1.0);
+impl Dielectric {
+    /// Reflects or refracts `ray`, picking the reflection with the Fresnel
+    /// probability only when `fresnel` is set. Total internal reflection
+    /// always reflects.
+    fn interact(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+        fresnel: bool,
+    ) -> Option<(Ray, Color)> {
+        // A back-face hit means the ray has just crossed the medium, so it
+        // gets absorbed according to the length of that segment.
+        let attenuation = if rec.front_face {
+            Color::new(1.0, 1.0, 1.0)
+        } else {
+            self.transmittance(rec.t * ray.direction.length())
+        };
+        let refraction_index = self.refraction_index_at(ray.wavelength);
         let ri = if rec.front_face {
-            1.0 / self.refraction_index
+            1.0 / refraction_index
         } else {
-            self.refraction_index
+            refraction_index
         };
 
         let unit_direction = ray.direction.unit_vector();
@@ -75,16 +276,514 @@ impl Material for Dielectric {
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = ri * sin_theta > 1.0;
-        let direction: Vec3;
-
-        if cannot_refract || self.reflectance(cos_theta, ri) > random_f64() {
-            direction = Vec3::reflect(&unit_direction, &rec.normal);
+        let reflects =
+            cannot_refract || (fresnel && self.reflectance(cos_theta, ri) > sampler.get_1d());
+        let direction = if reflects {
+            Vec3::reflect(&unit_direction, &rec.normal)
         } else {
-            direction = Vec3::refract(&unit_direction, &rec.normal, ri);
-        }
+            Vec3::refract(&unit_direction, &rec.normal, ri)
+        };
 
         let scattered = Ray::new(rec.p, direction);
 
         Some((scattered, attenuation))
     }
 }
+
+impl Material for Dielectric {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        self.interact(ray, rec, sampler, true)
+    }
+
+    fn transmit(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        self.interact(ray, rec, sampler, false)
+    }
+}
+
+/// Disney-style "principled" material. Parameters follow the glTF
+/// metallic-roughness model (plus the common KHR extensions), so assets
+/// authored for other renderers can be brought over without translation.
+///
+/// Every scatter picks a single lobe (clearcoat, metal, transmission,
+/// dielectric specular or diffuse + sheen) with a probability equal to its
+/// energy share, so the lobes never add up to more than the incoming light.
+#[derive(Clone, Copy)]
+pub struct Principled {
+    pub base_color: Color,
+    pub metallic: f64,
+    pub roughness: f64,
+    /// Scales the dielectric reflectance at normal incidence; 0.5 is a 4% F0.
+    pub specular: f64,
+    pub specular_tint: f64,
+    pub transmission: f64,
+    pub ior: f64,
+    pub clearcoat: f64,
+    pub clearcoat_roughness: f64,
+    pub sheen: f64,
+    pub sheen_tint: f64,
+}
+
+impl Default for Principled {
+    fn default() -> Self {
+        Self {
+            base_color: Color::new(0.8, 0.8, 0.8),
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            specular_tint: 0.0,
+            transmission: 0.0,
+            ior: 1.5,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.03,
+            sheen: 0.0,
+            sheen_tint: 0.5,
+        }
+    }
+}
+
+impl Principled {
+    /// Material from the three core glTF `pbrMetallicRoughness` factors.
+    pub fn metallic_roughness(base_color: Color, metallic: f64, roughness: f64) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            ..Default::default()
+        }
+    }
+
+    /// Base color normalized to unit luminance, used by the tint controls.
+    fn tint(&self) -> Color {
+        let lum = color::luminance(self.base_color);
+        if lum > 0.0 {
+            self.base_color / lum
+        } else {
+            Color::new(1.0, 1.0, 1.0)
+        }
+    }
+
+    /// Rough glass lobe: reflects or refracts off a sampled microfacet.
+    fn transmission_lobe(
+        &self,
+        unit_direction: &Vec3,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let ri = if rec.front_face {
+            1.0 / self.ior
+        } else {
+            self.ior
+        };
+        let h = sample_ggx_normal(&rec.normal, self.roughness, sampler.get_2d());
+
+        let cos_theta = (-*unit_direction).dot(&h).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let r0 = ((1.0 - ri) / (1.0 + ri)).powi(2);
+
+        if ri * sin_theta > 1.0 || schlick(cos_theta, r0) > sampler.get_1d() {
+            let direction = Vec3::reflect(unit_direction, &h);
+            if direction.dot(&rec.normal) <= 0.0 {
+                return None;
+            }
+            Some((Ray::new(rec.p, direction), Color::new(1.0, 1.0, 1.0)))
+        } else {
+            let direction = Vec3::refract(unit_direction, &h, ri);
+            if direction.dot(&rec.normal) >= 0.0 {
+                return None;
+            }
+            Some((Ray::new(rec.p, direction), self.base_color))
+        }
+    }
+}
+
+impl Material for Principled {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let unit_direction = ray.direction.unit_vector();
+        let cos_theta = (-unit_direction).dot(&rec.normal).clamp(0.0, 1.0);
+
+        // Clearcoat sits on top of everything else and is always a 1.5 IOR layer.
+        let clearcoat_weight = self.clearcoat * schlick(cos_theta, 0.04);
+        if sampler.get_1d() < clearcoat_weight {
+            return glossy_reflect(
+                &unit_direction,
+                rec,
+                self.clearcoat_roughness,
+                white,
+                sampler,
+            );
+        }
+
+        if sampler.get_1d() < self.metallic {
+            let fresnel = self.base_color + (white - self.base_color) * (1.0 - cos_theta).powi(5);
+            return glossy_reflect(&unit_direction, rec, self.roughness, fresnel, sampler);
+        }
+
+        if sampler.get_1d() < self.transmission {
+            return self.transmission_lobe(&unit_direction, rec, sampler);
+        }
+
+        let tint = self.tint();
+        let specular_weight = schlick(cos_theta, 0.08 * self.specular);
+        if sampler.get_1d() < specular_weight {
+            let specular_color = white * (1.0 - self.specular_tint) + tint * self.specular_tint;
+            return glossy_reflect(
+                &unit_direction,
+                rec,
+                self.roughness,
+                specular_color,
+                sampler,
+            );
+        }
+
+        let sheen_weight = self.sheen * (1.0 - cos_theta).powi(5);
+        let sheen_color = white * (1.0 - self.sheen_tint) + tint * self.sheen_tint;
+        let attenuation = self.base_color * (1.0 - sheen_weight) + sheen_color * sheen_weight;
+
+        let mut scatter_direction = rec.normal + Vec3::sample_unit_vector(sampler.get_2d());
+        if scatter_direction.near_zero() {
+            scatter_direction = rec.normal;
+        }
+        Some((Ray::new(rec.p, scatter_direction), attenuation))
+    }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.base_color
+    }
+}
+
+/// Reflects off a GGX microfacet, absorbing rays that end up below the surface.
+fn glossy_reflect(
+    unit_direction: &Vec3,
+    rec: &HitRecord,
+    roughness: f64,
+    attenuation: Color,
+    sampler: &mut dyn Sampler,
+) -> Option<(Ray, Color)> {
+    let h = sample_ggx_normal(&rec.normal, roughness, sampler.get_2d());
+    let direction = Vec3::reflect(unit_direction, &h);
+    if direction.dot(&rec.normal) > 0.0 {
+        Some((Ray::new(rec.p, direction), attenuation))
+    } else {
+        None
+    }
+}
+
+fn schlick(cosine: f64, r0: f64) -> f64 {
+    r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+}
+
+/// Samples a GGX microfacet normal around `normal`. `roughness` is the
+/// perceptual roughness, squared to get the distribution's alpha.
+fn sample_ggx_normal(normal: &Vec3, roughness: f64, (u1, u2): (f64, f64)) -> Vec3 {
+    let alpha = (roughness * roughness).max(1e-4);
+
+    let tan2_theta = alpha * alpha * u1 / (1.0 - u1).max(1e-12);
+    let cos_theta = 1.0 / (1.0 + tan2_theta).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * std::f64::consts::PI * u2;
+
+    let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+    Onb::new(normal).transform(&local)
+}
+
+/// Thin transparent film (soap, oil, anti-reflective coatings) on top of
+/// another material. Light reflected off the top and bottom of the film
+/// interferes, so the reflectance depends on wavelength, thickness and angle.
+///
+/// Spectral rays get the exact reflectance at their wavelength. RGB rays get
+/// the reflectance spectrum integrated against the CIE observer.
+///
+/// The film's reflectance already includes the interface to the base, so
+/// the light it lets through goes to `Material::transmit` of the base.
+#[derive(Clone)]
+pub struct ThinFilm {
+    pub base: Box<dyn Material>,
+    /// Film thickness in nanometers.
+    pub thickness: f64,
+    pub film_ior: f64,
+    /// Index of refraction of the material under the film. 1.0 gives a free
+    /// standing film such as a soap bubble.
+    pub base_ior: f64,
+}
+
+impl ThinFilm {
+    const RGB_WAVELENGTHS: usize = 16;
+
+    /// Airy reflectance of the film for unpolarized light at `wavelength`,
+    /// with `cos_theta` measured in the outside medium.
+    fn reflectance(&self, cos_theta: f64, wavelength: f64) -> f64 {
+        let sin2_theta = 1.0 - cos_theta * cos_theta;
+        let sin2_film = sin2_theta / (self.film_ior * self.film_ior);
+        let sin2_base = sin2_theta / (self.base_ior * self.base_ior);
+        if sin2_film >= 1.0 {
+            return 1.0;
+        }
+        let cos_film = (1.0 - sin2_film).sqrt();
+        let cos_base = (1.0 - sin2_base).max(0.0).sqrt();
+
+        let phase =
+            4.0 * std::f64::consts::PI * self.film_ior * self.thickness * cos_film / wavelength;
+        let airy = |r01: f64, r12: f64| {
+            let cross = 2.0 * r01 * r12 * phase.cos();
+            (r01 * r01 + r12 * r12 + cross) / (1.0 + r01 * r01 * r12 * r12 + cross)
+        };
+
+        let (n0, n1, n2) = (1.0, self.film_ior, self.base_ior);
+        let rs01 = (n0 * cos_theta - n1 * cos_film) / (n0 * cos_theta + n1 * cos_film);
+        let rs12 = (n1 * cos_film - n2 * cos_base) / (n1 * cos_film + n2 * cos_base);
+        let rp01 = (n1 * cos_theta - n0 * cos_film) / (n1 * cos_theta + n0 * cos_film);
+        let rp12 = (n2 * cos_film - n1 * cos_base) / (n2 * cos_film + n1 * cos_base);
+
+        0.5 * (airy(rs01, rs12) + airy(rp01, rp12))
+    }
+
+    fn reflectance_rgb(&self, cos_theta: f64) -> Color {
+        let n = Self::RGB_WAVELENGTHS;
+        let mut rgb = Color::default();
+        for i in 0..n {
+            let lambda = spectrum::sample_wavelength((i as f64 + 0.5) / n as f64);
+            rgb += spectrum::spectral_to_rgb(self.reflectance(cos_theta, lambda), lambda);
+        }
+        let rgb = rgb / n as f64;
+        Color::new(
+            rgb.x.clamp(0.0, 1.0),
+            rgb.y.clamp(0.0, 1.0),
+            rgb.z.clamp(0.0, 1.0),
+        )
+    }
+}
+
+impl Material for ThinFilm {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let unit_direction = ray.direction.unit_vector();
+        let cos_theta = (-unit_direction).dot(&rec.normal).clamp(0.0, 1.0);
+
+        let reflectance = match ray.wavelength {
+            Some(lambda) => {
+                let r = self.reflectance(cos_theta, lambda);
+                Color::new(r, r, r)
+            }
+            None => self.reflectance_rgb(cos_theta),
+        };
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        // Pick the film or the base with the film's average reflectance and
+        // reweight, so colored reflectances stay unbiased.
+        let p_reflect = ((reflectance.x + reflectance.y + reflectance.z) / 3.0).clamp(0.0, 1.0);
+        if sampler.get_1d() < p_reflect {
+            let direction = Vec3::reflect(&unit_direction, &rec.normal);
+            Some((Ray::new(rec.p, direction), reflectance / p_reflect))
+        } else {
+            let (scattered, attenuation) = self.base.transmit(ray, rec, sampler)?;
+            Some((
+                scattered,
+                attenuation * (white - reflectance) / (1.0 - p_reflect),
+            ))
+        }
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.base.albedo(rec)
+    }
+}
+
+/// Blends two materials, Blender "Mix Shader" style: a weight of 0 is all
+/// `a`, 1 is all `b`. Each scatter picks one of them, so the mix is energy
+/// conserving as long as both inputs are.
+#[derive(Clone)]
+pub struct MixMaterial {
+    pub a: Box<dyn Material>,
+    pub b: Box<dyn Material>,
+    /// Weight of `b`, read as the luminance of the texture at the hit point.
+    pub weight: Box<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new(a: Box<dyn Material>, b: Box<dyn Material>, weight: f64) -> Self {
+        Self {
+            a,
+            b,
+            weight: Box::new(SolidColor::new(weight, weight, weight)),
+        }
+    }
+
+    pub fn textured(a: Box<dyn Material>, b: Box<dyn Material>, weight: Box<dyn Texture>) -> Self {
+        Self { a, b, weight }
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let weight = color::luminance(self.weight.value(rec.u, rec.v, &rec.p));
+        if sampler.get_1d() < weight {
+            self.b.scatter(ray, rec, sampler)
+        } else {
+            self.a.scatter(ray, rec, sampler)
+        }
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        let weight = color::luminance(self.weight.value(rec.u, rec.v, &rec.p));
+        self.a.albedo(rec) * (1.0 - weight) + self.b.albedo(rec) * weight
+    }
+}
+
+/// Dielectric coating (varnish, lacquer, car paint clearcoat) layered over
+/// any other material. The coating reflects with its Fresnel probability and
+/// passes the remaining light on to the base.
+#[derive(Clone)]
+pub struct Coated {
+    pub base: Box<dyn Material>,
+    pub ior: f64,
+    pub roughness: f64,
+}
+
+impl Coated {
+    pub fn new(base: Box<dyn Material>) -> Self {
+        Self {
+            base,
+            ior: 1.5,
+            roughness: 0.0,
+        }
+    }
+}
+
+impl Material for Coated {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        let unit_direction = ray.direction.unit_vector();
+        let cos_theta = (-unit_direction).dot(&rec.normal).clamp(0.0, 1.0);
+        let r0 = ((1.0 - self.ior) / (1.0 + self.ior)).powi(2);
+
+        if sampler.get_1d() < schlick(cos_theta, r0) {
+            glossy_reflect(
+                &unit_direction,
+                rec,
+                self.roughness,
+                Color::new(1.0, 1.0, 1.0),
+                sampler,
+            )
+        } else {
+            self.base.scatter(ray, rec, sampler)
+        }
+    }
+
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.base.albedo(rec)
+    }
+}
+
+/// Area light: emits `emit` from its front face and absorbs everything
+/// that hits it.
+#[derive(Clone, Copy, Default)]
+pub struct DiffuseLight {
+    pub emit: Color,
+}
+
+impl Material for DiffuseLight {
+    fn scatter(
+        &self,
+        _ray: &Ray,
+        _rec: &HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        None
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        if rec.front_face {
+            self.emit
+        } else {
+            Color::default()
+        }
+    }
+}
+
+/// Stand-in for something that will be composited in front of the render
+/// later. The camera sees a transparent hole; other rays see a black surface.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Holdout;
+
+impl Material for Holdout {
+    fn scatter(
+        &self,
+        _ray: &Ray,
+        _rec: &HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        None
+    }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    fn matte(&self) -> Option<Matte> {
+        Some(Matte::Holdout)
+    }
+}
+
+/// Stand-in for the ground of a background plate: a diffuse surface that
+/// lights the scene like any other, but that the camera only sees as the
+/// shadows falling on it. Meant for renders with a `Transparent`
+/// environment, so the shadows can be laid over the plate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShadowCatcher {
+    pub albedo: Color,
+}
+
+impl Material for ShadowCatcher {
+    fn scatter(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> Option<(Ray, Color)> {
+        Lambertian {
+            albedo: self.albedo,
+        }
+        .scatter(ray, rec, sampler)
+    }
+
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        self.albedo
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        Lambertian::default().scattering_pdf(ray, rec, scattered)
+    }
+
+    fn matte(&self) -> Option<Matte> {
+        Some(Matte::ShadowCatcher)
+    }
+}
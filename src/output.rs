@@ -0,0 +1,160 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use image::{
+    DynamicImage, ImageError, ImageResult, Rgb, Rgb32FImage, RgbImage, Rgba, Rgba32FImage,
+    RgbaImage,
+};
+
+use crate::{
+    color::{self, Color},
+    tonemap::DisplayTransform,
+};
+
+/// Converts a linear framebuffer from `Camera::render` into an opaque 8-bit
+/// display image using `transform`. Transparent areas come out black.
+pub fn to_rgb8(image: &Rgba32FImage, transform: &DisplayTransform) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, _] = image.get_pixel(x, y).0;
+        let linear = Color::new(r as f64, g as f64, b as f64);
+        Rgb(color::quantize(transform.apply(linear)))
+    })
+}
+
+/// How color relates to alpha in images with an alpha channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// What the format expects: premultiplied for OpenEXR, straight for
+    /// 8-bit formats.
+    #[default]
+    Auto,
+    /// Color already multiplied by alpha, as the renderer produces it.
+    Premultiplied,
+    /// Color independent of alpha.
+    Straight,
+}
+
+/// Like `to_rgb8`, but keeps the alpha channel. The display transform is
+/// applied to the unpremultiplied color; `Premultiplied` multiplies the
+/// encoded result by alpha again.
+pub fn to_rgba8(image: &Rgba32FImage, transform: &DisplayTransform, mode: AlphaMode) -> RgbaImage {
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let alpha = (a as f64).clamp(0.0, 1.0);
+        let straight = if a > 0.0 {
+            Color::new(r as f64, g as f64, b as f64) / a as f64
+        } else {
+            Color::default()
+        };
+        let mut display = transform.apply(straight);
+        if mode == AlphaMode::Premultiplied {
+            display = display * alpha;
+        }
+        let [r, g, b] = color::quantize(display);
+        Rgba([r, g, b, (alpha * 255.0).round() as u8])
+    })
+}
+
+/// Divides the color of a premultiplied image by its alpha. Fully
+/// transparent pixels become black.
+pub fn unpremultiply(image: &Rgba32FImage) -> Rgba32FImage {
+    Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        if a > 0.0 {
+            Rgba([r / a, g / a, b / a, a])
+        } else {
+            Rgba([0.0; 4])
+        }
+    })
+}
+
+/// False-color view of a sample count buffer (the `sample_count` AOV), from
+/// black for the fewest samples through red and yellow to white for the most.
+pub fn sample_heatmap(sample_count: &Rgb32FImage) -> RgbImage {
+    const RAMP: [[f64; 3]; 5] = [
+        [0.0, 0.0, 0.0],
+        [0.2, 0.0, 0.6],
+        [0.9, 0.1, 0.1],
+        [1.0, 0.85, 0.0],
+        [1.0, 1.0, 1.0],
+    ];
+
+    let counts = || sample_count.pixels().map(|p| p.0[0]);
+    let min = counts().fold(f32::INFINITY, f32::min);
+    let max = counts().fold(0.0, f32::max);
+    let span = (max - min).max(1.0);
+
+    RgbImage::from_fn(sample_count.width(), sample_count.height(), |x, y| {
+        let t = ((sample_count.get_pixel(x, y).0[0] - min) / span) as f64;
+        let scaled = t * (RAMP.len() - 1) as f64;
+        let i = (scaled as usize).min(RAMP.len() - 2);
+        let f = scaled - i as f64;
+        let lo = Color::from(RAMP[i]);
+        let hi = Color::from(RAMP[i + 1]);
+        Rgb(color::quantize(lo * (1.0 - f) + hi * f))
+    })
+}
+
+/// Writes the linear framebuffer untouched. The format is picked from the
+/// extension: `.exr` (OpenEXR, with alpha as `mode` says), `.hdr` (Radiance
+/// RGBE) or `.pfm`. The last two have no alpha channel and get the image
+/// composited over black.
+pub fn save_linear(
+    image: &Rgba32FImage,
+    mode: AlphaMode,
+    path: impl AsRef<Path>,
+) -> ImageResult<()> {
+    let path = path.as_ref();
+    let rgb = || DynamicImage::ImageRgba32F(image.clone()).into_rgb32f();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("pfm") => save_pfm(&rgb(), path),
+        Some(ext) if ext.eq_ignore_ascii_case("hdr") => rgb().save(path),
+        _ if mode == AlphaMode::Straight => unpremultiply(image).save(path),
+        _ => image.save(path),
+    }
+}
+
+/// Saves `image` in the format picked from the extension: linear radiance
+/// for `.exr`, `.hdr` and `.pfm`, otherwise 8-bit through `transform`, with
+/// alpha in `mode` unless the format (JPEG) has none.
+pub fn save(
+    image: &Rgba32FImage,
+    transform: &DisplayTransform,
+    mode: AlphaMode,
+    path: impl AsRef<Path>,
+) -> ImageResult<()> {
+    let path = path.as_ref();
+    let has_extension = |extensions: &[&str]| {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+    };
+    if has_extension(&["exr", "hdr", "pfm"]) {
+        save_linear(image, mode, path)
+    } else if has_extension(&["jpg", "jpeg"]) {
+        to_rgb8(image, transform).save(path)
+    } else {
+        to_rgba8(image, transform, mode).save(path)
+    }
+}
+
+/// Portable float map: little-endian, rows stored bottom to top.
+pub fn save_pfm(image: &Rgb32FImage, path: impl AsRef<Path>) -> ImageResult<()> {
+    let mut writer = BufWriter::new(File::create(path).map_err(ImageError::IoError)?);
+    write_pfm(image, &mut writer).map_err(ImageError::IoError)
+}
+
+fn write_pfm(image: &Rgb32FImage, writer: &mut impl Write) -> std::io::Result<()> {
+    write!(writer, "PF\n{} {}\n-1.0\n", image.width(), image.height())?;
+    for row in image.rows().rev() {
+        for pixel in row {
+            for channel in pixel.0 {
+                writer.write_all(&channel.to_le_bytes())?;
+            }
+        }
+    }
+    writer.flush()
+}
@@ -0,0 +1,156 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+/// How the camera maps image positions to rays. Angles are in degrees.
+#[derive(Clone, Copy, Debug)]
+pub enum Projection {
+    /// Pinhole camera. `vfov` is the vertical field of view; the default of
+    /// 90° is what the renderer originally used.
+    Perspective { vfov: f64 },
+    /// Parallel rays along the view direction, for technical and isometric
+    /// views. `height` is the height of the view in scene units.
+    Orthographic { height: f64 },
+    /// Fisheye whose distance from the image center grows linearly with the
+    /// angle off axis. The image circle spans `fov` and fits the shorter side
+    /// of the image; outside it the render is transparent black.
+    EquidistantFisheye { fov: f64 },
+    /// Fisheye that keeps areas in proportion to solid angle, like most
+    /// real fisheye lenses. Framed like `EquidistantFisheye`.
+    EquisolidFisheye { fov: f64 },
+    /// The full sphere around the camera in latitude-longitude layout, the
+    /// format of `EnvironmentMap`. Use a 2:1 aspect ratio.
+    Equirectangular,
+    /// 360° around the vertical axis, with a flat (perspective) mapping of
+    /// the `vfov` vertical field of view.
+    Cylindrical { vfov: f64 },
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective { vfov: 90.0 }
+    }
+}
+
+impl Projection {
+    /// Ray through the image position `(s, t)`, both in [0, 1] from the top
+    /// left corner, for an image `aspect_ratio` times wider than it is tall.
+    /// Returns the ray origin relative to the camera and its direction, in
+    /// camera space: +X right, +Y up, looking down -Z. `None` means the
+    /// position lies outside what the projection covers.
+    pub fn ray(&self, (s, t): (f64, f64), aspect_ratio: f64) -> Option<(Vec3, Vec3)> {
+        // Centered coordinates, -1 to 1 along the image height.
+        let x = (2.0 * s - 1.0) * aspect_ratio;
+        let y = 1.0 - 2.0 * t;
+        let origin = Vec3::default();
+
+        match *self {
+            Projection::Perspective { vfov } => {
+                let h = (vfov.to_radians() / 2.0).tan();
+                Some((origin, Vec3::new(x * h, y * h, -1.0)))
+            }
+            Projection::Orthographic { height } => {
+                let origin = Vec3::new(x, y, 0.0) * (height / 2.0);
+                Some((origin, Vec3::new(0.0, 0.0, -1.0)))
+            }
+            Projection::EquidistantFisheye { fov } | Projection::EquisolidFisheye { fov } => {
+                // Radius 1 is the edge of the image circle.
+                let (x, y) = (x / aspect_ratio.min(1.0), y / aspect_ratio.min(1.0));
+                let r = x.hypot(y);
+                if r > 1.0 {
+                    return None;
+                }
+                let half_fov = fov.to_radians() / 2.0;
+                let theta = match self {
+                    Projection::EquidistantFisheye { .. } => r * half_fov,
+                    _ => 2.0 * (r * (half_fov / 2.0).sin()).asin(),
+                };
+                let (sin, cos) = theta.sin_cos();
+                let (dx, dy) = if r > 0.0 { (x / r, y / r) } else { (0.0, 0.0) };
+                Some((origin, Vec3::new(sin * dx, sin * dy, -cos)))
+            }
+            Projection::Equirectangular => {
+                let phi = (s - 0.5) * 2.0 * PI;
+                let latitude = (0.5 - t) * PI;
+                let (sin, cos) = latitude.sin_cos();
+                Some((origin, Vec3::new(cos * phi.sin(), sin, -cos * phi.cos())))
+            }
+            Projection::Cylindrical { vfov } => {
+                let phi = (s - 0.5) * 2.0 * PI;
+                let h = y * (vfov.to_radians() / 2.0).tan();
+                Some((origin, Vec3::new(phi.sin(), h, -phi.cos())))
+            }
+        }
+    }
+}
+
+/// Arrangement of the two eyes in a stereo image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StereoLayout {
+    /// Left eye on the left, right eye on the right.
+    #[default]
+    SideBySide,
+    /// Left eye on top, right eye below, the usual layout for 360° video.
+    OverUnder,
+}
+
+/// Renders a left and a right eye view into one image.
+///
+/// With 360° projections (`Equirectangular`, `Cylindrical`) the eyes circle
+/// the camera center, always side by side relative to the direction being
+/// looked at: omni-directional stereo (ODS), which headsets can show in any
+/// direction. Other projections offset both eyes along the camera's right
+/// axis.
+#[derive(Clone, Copy, Debug)]
+pub struct Stereo {
+    /// Distance between the eyes (interpupillary distance), in scene units.
+    pub eye_separation: f64,
+    /// Distance at which the two eyes' rays cross, where objects appear at
+    /// the depth of the screen. Infinity keeps the eyes parallel.
+    pub convergence: f64,
+    pub layout: StereoLayout,
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self {
+            eye_separation: 0.064,
+            convergence: f64::INFINITY,
+            layout: StereoLayout::default(),
+        }
+    }
+}
+
+impl Stereo {
+    /// Moves a ray from `projection` (in camera space, as returned by
+    /// `Projection::ray`) over to one eye: `side` is -1 for the left eye and
+    /// 1 for the right one.
+    pub fn eye_ray(
+        &self,
+        projection: &Projection,
+        side: f64,
+        (origin, direction): (Vec3, Vec3),
+    ) -> (Vec3, Vec3) {
+        let offset = match projection {
+            Projection::Equirectangular | Projection::Cylindrical { .. } => {
+                // Perpendicular to the horizontal part of the view direction,
+                // to its right.
+                let right = Vec3::new(-direction.z, 0.0, direction.x);
+                if right.near_zero() {
+                    Vec3::default()
+                } else {
+                    right.unit_vector()
+                }
+            }
+            _ => Vec3::new(1.0, 0.0, 0.0),
+        };
+        let eye = origin + offset * (side * self.eye_separation / 2.0);
+
+        if self.convergence.is_finite() {
+            let target = origin + direction.unit_vector() * self.convergence;
+            (eye, target - eye)
+        } else {
+            (eye, direction)
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    hittable::{HitRecord, Hittable},
+    hittable_list::HittableList,
+    interval::Interval,
+    material::Material,
+    ray::{Point3, Ray},
+    vec3::Vec3,
+};
+
+/// Parallelogram with a corner at `q` and sides `u` and `v`. Its front
+/// faces along `u × v`.
+#[derive(Clone)]
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    /// `n / (n · n)` for the plane normal `n = u × v`, which turns a point
+    /// on the plane into its (u, v) coordinates.
+    w: Vec3,
+    normal: Vec3,
+    d: f64,
+    mat: Box<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: Box<dyn Material>) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit_vector();
+        Self {
+            q,
+            u,
+            v,
+            w: n / n.dot(&n),
+            normal,
+            d: normal.dot(&q),
+            mat,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let denom = self.normal.dot(&ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = (self.d - self.normal.dot(&ray.origin)) / denom;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let p = ray.at(t);
+        let planar = p - self.q;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let mut rec = HitRecord::new();
+        rec.t = t;
+        rec.p = p;
+        (rec.u, rec.v) = (alpha, beta);
+        rec.set_face_normal(ray, &self.normal);
+        rec.mat = self.mat.clone();
+        Some(rec)
+    }
+}
+
+/// Closed box between the opposite corners `a` and `b`, made of six quads
+/// facing out.
+pub fn cuboid(a: Point3, b: Point3, mat: Box<dyn Material>) -> HittableList {
+    let min = Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+    let max = Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+    let dx = Vec3::new(max.x - min.x, 0.0, 0.0);
+    let dy = Vec3::new(0.0, max.y - min.y, 0.0);
+    let dz = Vec3::new(0.0, 0.0, max.z - min.z);
+
+    let mut sides = HittableList::default();
+    let mut add = |q: Point3, u: Vec3, v: Vec3| sides.add(Quad::new(q, u, v, mat.clone()));
+    add(Point3::new(min.x, min.y, max.z), dx, dy); // front
+    add(Point3::new(max.x, min.y, max.z), -dz, dy); // right
+    add(Point3::new(max.x, min.y, min.z), -dx, dy); // back
+    add(Point3::new(min.x, min.y, min.z), dz, dy); // left
+    add(Point3::new(min.x, max.y, max.z), dx, -dz); // top
+    add(Point3::new(min.x, min.y, min.z), dx, dz); // bottom
+    sides
+}
@@ -1,15 +1,21 @@
-use crate::Vec3;
+use crate::vec3::Vec3;
 
 pub type Point3 = Vec3;
 
 pub struct Ray {
     pub origin: Vec3,
     pub direction: Vec3,
+    /// Wavelength in nanometers carried by the ray in spectral mode.
+    pub wavelength: Option<f64>,
 }
 
 impl Ray {
     pub fn new(origin: Vec3, direction: Vec3) -> Ray {
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            wavelength: None,
+        }
     }
 
     #[inline]
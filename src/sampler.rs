@@ -0,0 +1,360 @@
+/// Source of the random numbers used while rendering.
+///
+/// The camera calls `start_pixel_sample` before tracing each camera ray and
+/// `start_bounce` at every hit, so a given bounce always draws from the same
+/// dimensions of the sequence no matter how many numbers earlier bounces
+/// consumed. Low-discrepancy samplers rely on that to keep each dimension
+/// well distributed across the samples of a pixel.
+///
+/// All samplers are deterministic functions of the seed, the pixel, the
+/// sample index and the dimension.
+pub trait Sampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32);
+
+    fn start_bounce(&mut self, bounce: u32);
+
+    fn get_1d(&mut self) -> f64;
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.get_1d(), self.get_1d())
+    }
+}
+
+/// Dimensions reserved for the camera ray (pixel jitter, lens, wavelength).
+pub const CAMERA_DIMENSIONS: u32 = 8;
+/// Dimensions reserved for each bounce. Materials that need more than this
+/// spill over into the next bounce's dimensions.
+pub const BOUNCE_DIMENSIONS: u32 = 16;
+
+/// Which `Sampler` the camera builds for a render.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SamplerKind {
+    /// Independent uniform random numbers (white noise).
+    #[default]
+    Independent,
+    /// Jittered stratification of each dimension across the pixel's samples.
+    Stratified,
+    /// Owen-scrambled Halton sequence. Deep bounces land on large prime
+    /// bases, which only pay off with many samples per pixel.
+    Halton,
+    /// Sobol (0, 2) points, padded across dimension pairs, with hash-based
+    /// approximate Owen scrambling. The scrambling is seeded per pixel, so
+    /// neighboring pixels' errors are uncorrelated (white noise).
+    Sobol,
+}
+
+impl SamplerKind {
+    /// `samples_per_pixel` is the expected number of samples for each pixel;
+    /// only the stratified sampler needs to know it up front.
+    pub fn build(self, seed: u64, samples_per_pixel: u32) -> Box<dyn Sampler> {
+        let state = SampleState {
+            seed,
+            ..Default::default()
+        };
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler { state }),
+            SamplerKind::Stratified => Box::new(StratifiedSampler {
+                state,
+                samples_per_pixel: samples_per_pixel.max(1),
+            }),
+            SamplerKind::Halton => Box::new(HaltonSampler { state }),
+            SamplerKind::Sobol => Box::new(SobolSampler { state }),
+        }
+    }
+}
+
+/// Where in the sequence a sampler currently is.
+#[derive(Clone, Copy, Debug, Default)]
+struct SampleState {
+    seed: u64,
+    x: u32,
+    y: u32,
+    index: u32,
+    dimension: u32,
+}
+
+impl SampleState {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.x = x;
+        self.y = y;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn start_bounce(&mut self, bounce: u32) {
+        self.dimension = CAMERA_DIMENSIONS + bounce * BOUNCE_DIMENSIONS;
+    }
+
+    fn next_dimension(&mut self) -> u32 {
+        self.dimension += 1;
+        self.dimension - 1
+    }
+
+    /// Hash of the pixel and dimension, shared by all samples of the pixel.
+    fn pixel_hash(&self, dimension: u32) -> u64 {
+        hash(&[self.seed, self.x as u64, self.y as u64, dimension as u64])
+    }
+
+    /// Hash that is different for every sample and dimension.
+    fn sample_hash(&self, dimension: u32) -> u64 {
+        hash(&[
+            self.seed,
+            self.x as u64,
+            self.y as u64,
+            self.index as u64,
+            dimension as u64,
+        ])
+    }
+}
+
+pub struct IndependentSampler {
+    state: SampleState,
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start_pixel_sample(x, y, index);
+    }
+
+    fn start_bounce(&mut self, bounce: u32) {
+        self.state.start_bounce(bounce);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.state.next_dimension();
+        to_unit_f64(self.state.sample_hash(dimension))
+    }
+}
+
+pub struct StratifiedSampler {
+    state: SampleState,
+    samples_per_pixel: u32,
+}
+
+impl StratifiedSampler {
+    fn stratum(&self, dimension: u32) -> u32 {
+        let n = self.samples_per_pixel;
+        let seed = self.state.pixel_hash(dimension) as u32;
+        permutation_element(self.state.index % n, n, seed)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start_pixel_sample(x, y, index);
+    }
+
+    fn start_bounce(&mut self, bounce: u32) {
+        self.state.start_bounce(bounce);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.state.next_dimension();
+        let jitter = to_unit_f64(self.state.sample_hash(dimension));
+        (self.stratum(dimension) as f64 + jitter) / self.samples_per_pixel as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let n = self.samples_per_pixel;
+        let side = (n as f64).sqrt() as u32;
+        if side * side != n {
+            return (self.get_1d(), self.get_1d());
+        }
+
+        // Square sample counts get a proper 2D jittered grid.
+        let dimension = self.state.next_dimension();
+        self.state.next_dimension();
+        let stratum = self.stratum(dimension);
+        let jx = to_unit_f64(self.state.sample_hash(dimension));
+        let jy = to_unit_f64(self.state.sample_hash(dimension + 1));
+        (
+            ((stratum % side) as f64 + jx) / side as f64,
+            ((stratum / side) as f64 + jy) / side as f64,
+        )
+    }
+}
+
+pub struct HaltonSampler {
+    state: SampleState,
+}
+
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start_pixel_sample(x, y, index);
+    }
+
+    fn start_bounce(&mut self, bounce: u32) {
+        self.state.start_bounce(bounce);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.state.next_dimension();
+        let Some(&base) = PRIMES.get(dimension as usize) else {
+            return to_unit_f64(self.state.sample_hash(dimension));
+        };
+        // Owen scrambling per pixel decorrelates neighboring pixels and
+        // fixes the poor 2D projections of the higher prime bases.
+        owen_scrambled_radical_inverse(self.state.index, base, self.state.pixel_hash(dimension))
+    }
+}
+
+pub struct SobolSampler {
+    state: SampleState,
+}
+
+impl SobolSampler {
+    /// Owen-scrambled, index-shuffled 2D Sobol point for one dimension pair,
+    /// following Burley, "Practical Hash-based Owen Scrambling" (2020).
+    fn sobol_2d(&self, dimension: u32) -> (f64, f64) {
+        let mut seed = self.state.pixel_hash(dimension) as u32;
+        let index = nested_uniform_scramble(self.state.index, seed);
+
+        seed = hash_u32(seed);
+        let x = nested_uniform_scramble(sobol_dimension_0(index), seed);
+        seed = hash_u32(seed);
+        let y = nested_uniform_scramble(sobol_dimension_1(index), seed);
+
+        (u32_to_unit_f64(x), u32_to_unit_f64(y))
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.state.start_pixel_sample(x, y, index);
+    }
+
+    fn start_bounce(&mut self, bounce: u32) {
+        self.state.start_bounce(bounce);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let dimension = self.state.next_dimension();
+        self.sobol_2d(dimension).0
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let dimension = self.state.next_dimension();
+        self.state.next_dimension();
+        self.sobol_2d(dimension)
+    }
+}
+
+/// Radical inverse of `index` with every digit permuted based on the digits
+/// before it (Owen scrambling), after pbrt-v4.
+fn owen_scrambled_radical_inverse(mut index: u32, base: u32, seed: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_m = 1.0;
+    let mut prefix_hash = seed;
+    let mut result = 0.0;
+    // Keep generating digits (including leading zeros of `index`) until they
+    // no longer change the result.
+    while 1.0 - (base - 1) as f64 * inv_base_m < 1.0 {
+        let next = index / base;
+        let digit = index - next * base;
+        let digit = permutation_element(digit, base, prefix_hash as u32);
+        prefix_hash = mix64(prefix_hash ^ digit as u64);
+        inv_base_m *= inv_base;
+        result += digit as f64 * inv_base_m;
+        index = next;
+    }
+    result.min(1.0 - f64::EPSILON)
+}
+
+/// Van der Corput sequence, the first Sobol dimension.
+fn sobol_dimension_0(index: u32) -> u32 {
+    index.reverse_bits()
+}
+
+/// Second Sobol dimension, generated by the primitive polynomial x + 1.
+fn sobol_dimension_1(mut index: u32) -> u32 {
+    let mut result = 0;
+    let mut direction = 1u32 << 31;
+    while index != 0 {
+        if index & 1 != 0 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    result
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x
+}
+
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+/// Element `i` of a random permutation of `0..l` chosen by `p`, without
+/// storing the permutation (Kensler, "Correlated Multi-Jittered Sampling").
+fn permutation_element(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+    (i.wrapping_add(p)) % l
+}
+
+/// SplitMix64 finalizer.
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+pub(crate) fn hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .fold(0x9e37_79b9_7f4a_7c15, |h, &v| mix64(h ^ mix64(v)))
+}
+
+fn hash_u32(x: u32) -> u32 {
+    mix64(x as u64) as u32
+}
+
+fn to_unit_f64(bits: u64) -> f64 {
+    (bits >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+fn u32_to_unit_f64(bits: u32) -> f64 {
+    bits as f64 * (1.0 / (1u64 << 32) as f64)
+}
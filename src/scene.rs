@@ -0,0 +1,394 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::{Constant, Gradient},
+    hittable_list::HittableList,
+    material::{
+        Coated, Dielectric, DiffuseLight, Lambertian, Material, Metal, MixMaterial, Principled,
+        ThinFilm,
+    },
+    projection::Projection,
+    quad::{cuboid, Quad},
+    ray::Point3,
+    sky::SunSky,
+    sphere::Sphere,
+    texture::CheckerTexture,
+    transform::{Transform, Transformed},
+    vec3::Vec3,
+};
+
+/// A world together with a camera set up to look at it. The camera's
+/// settings can be changed further before building it.
+pub struct Scene {
+    pub world: HittableList,
+    pub camera: CameraBuilder,
+}
+
+/// Names `by_name` knows, the first being the default.
+pub const NAMES: [&str; 5] = [
+    "three-spheres",
+    "random-spheres",
+    "cornell-box",
+    "material-grid",
+    "material-preview",
+];
+
+/// The preset called `name`, see `NAMES`.
+pub fn by_name(name: &str) -> Option<Scene> {
+    match name {
+        "three-spheres" => Some(three_spheres()),
+        "random-spheres" => Some(random_spheres(0)),
+        "cornell-box" => Some(cornell_box()),
+        "material-grid" => Some(material_grid()),
+        "material-preview" => Some(material_preview(Box::new(Principled {
+            base_color: Color::new(0.8, 0.25, 0.1),
+            roughness: 0.3,
+            clearcoat: 1.0,
+            ..Default::default()
+        }))),
+        _ => None,
+    }
+}
+
+fn sphere(center: Point3, radius: f64, mat: impl Material + 'static) -> Sphere {
+    Sphere {
+        center,
+        radius,
+        mat: Box::new(mat),
+    }
+}
+
+/// Glass, diffuse and metal spheres on a yellow ground, the scene of the
+/// first chapters of "Ray Tracing in One Weekend".
+pub fn three_spheres() -> Scene {
+    let mut world = HittableList::default();
+    world.add(sphere(
+        Point3::new(0.0, -100.5, -1.0),
+        100.0,
+        Lambertian {
+            albedo: Color::new(0.8, 0.8, 0.0),
+        },
+    ));
+    world.add(sphere(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.5,
+        Dielectric::new(1.50),
+    ));
+    world.add(sphere(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.4,
+        Dielectric::new(1.0 / 1.50),
+    ));
+    world.add(sphere(
+        Point3::new(0.0, 0.0, -1.2),
+        0.5,
+        Lambertian {
+            albedo: Color::new(0.1, 0.2, 0.5),
+        },
+    ));
+    world.add(sphere(
+        Point3::new(1.0, 0.0, -1.0),
+        0.5,
+        Metal {
+            albedo: Color::new(0.8, 0.6, 0.2),
+            fuzz: 0.0,
+        },
+    ));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .max_depth(100);
+    Scene { world, camera }
+}
+
+/// The final render of "Ray Tracing in One Weekend": three large spheres
+/// among a field of small random ones, laid out from `seed`. The camera
+/// has no defocus blur.
+pub fn random_spheres(seed: u64) -> Scene {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let random_color = |rng: &mut StdRng, min: f64, max: f64| {
+        Color::new(
+            rng.gen_range(min..max),
+            rng.gen_range(min..max),
+            rng.gen_range(min..max),
+        )
+    };
+
+    let mut world = HittableList::default();
+    world.add(sphere(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        },
+    ));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat: f64 = rng.gen();
+            let center = Point3::new(
+                a as f64 + 0.9 * rng.gen::<f64>(),
+                0.2,
+                b as f64 + 0.9 * rng.gen::<f64>(),
+            );
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() <= 0.9 {
+                continue;
+            }
+            let mat: Box<dyn Material> = if choose_mat < 0.8 {
+                let albedo = random_color(&mut rng, 0.0, 1.0) * random_color(&mut rng, 0.0, 1.0);
+                Box::new(Lambertian { albedo })
+            } else if choose_mat < 0.95 {
+                Box::new(Metal {
+                    albedo: random_color(&mut rng, 0.5, 1.0),
+                    fuzz: rng.gen_range(0.0..0.5),
+                })
+            } else {
+                Box::new(Dielectric::new(1.5))
+            };
+            world.add(Sphere {
+                center,
+                radius: 0.2,
+                mat,
+            });
+        }
+    }
+
+    world.add(sphere(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Dielectric::new(1.5),
+    ));
+    world.add(sphere(
+        Point3::new(-4.0, 1.0, 0.0),
+        1.0,
+        Lambertian {
+            albedo: Color::new(0.4, 0.2, 0.1),
+        },
+    ));
+    world.add(sphere(
+        Point3::new(4.0, 1.0, 0.0),
+        1.0,
+        Metal {
+            albedo: Color::new(0.7, 0.6, 0.5),
+            fuzz: 0.0,
+        },
+    ));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .projection(Projection::Perspective { vfov: 20.0 })
+        .center(Point3::new(13.0, 2.0, 3.0))
+        .look_at(Point3::new(0.0, 0.0, 0.0));
+    Scene { world, camera }
+}
+
+/// The Cornell box of "Ray Tracing: The Next Week": red and green side
+/// walls, a square light in the ceiling and two turned boxes. Only the
+/// light shines; everything else is dark.
+pub fn cornell_box() -> Scene {
+    let red = Lambertian {
+        albedo: Color::new(0.65, 0.05, 0.05),
+    };
+    let white = Lambertian {
+        albedo: Color::new(0.73, 0.73, 0.73),
+    };
+    let green = Lambertian {
+        albedo: Color::new(0.12, 0.45, 0.15),
+    };
+    let light = DiffuseLight {
+        emit: Color::new(15.0, 15.0, 15.0),
+    };
+
+    let mut world = HittableList::default();
+    let mut wall = |q: Point3, u: Vec3, v: Vec3, mat: Box<dyn Material>| {
+        world.add(Quad::new(q, u, v, mat));
+    };
+    wall(
+        Point3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        Box::new(green),
+    );
+    wall(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Box::new(red),
+    );
+    wall(
+        Point3::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+        Box::new(light),
+    );
+    wall(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Box::new(white),
+    );
+    wall(
+        Point3::new(555.0, 555.0, 555.0),
+        Vec3::new(-555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -555.0),
+        Box::new(white),
+    );
+    wall(
+        Point3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Box::new(white),
+    );
+
+    let tall = cuboid(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 330.0, 165.0),
+        Box::new(white),
+    );
+    world.add(Transformed::new(
+        tall,
+        Transform {
+            translation: Vec3::new(265.0, 0.0, 295.0),
+            rotation: Vec3::new(0.0, 15.0, 0.0),
+            ..Default::default()
+        },
+    ));
+    let short = cuboid(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(165.0, 165.0, 165.0),
+        Box::new(white),
+    );
+    world.add(Transformed::new(
+        short,
+        Transform {
+            translation: Vec3::new(130.0, 0.0, 65.0),
+            rotation: Vec3::new(0.0, -18.0, 0.0),
+            ..Default::default()
+        },
+    ));
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1.0)
+        .image_width(400)
+        .samples_per_pixel(200)
+        .max_depth(50)
+        .environment(Constant::default())
+        .projection(Projection::Perspective { vfov: 40.0 })
+        .center(Point3::new(278.0, 278.0, -800.0))
+        .look_at(Point3::new(278.0, 278.0, 0.0));
+    Scene { world, camera }
+}
+
+/// Three rows of five spheres on a grey floor: plastic and then metal
+/// going from smooth to rough, and a row of glass, frosted glass, a
+/// tinted dielectric, a lacquered diffuse and an oily thin film.
+pub fn material_grid() -> Scene {
+    let mut world = HittableList::default();
+    world.add(sphere(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        },
+    ));
+
+    let special: [Box<dyn Material>; 5] = [
+        Box::new(Dielectric::new(1.5)),
+        Box::new(Principled {
+            base_color: Color::new(0.9, 0.9, 0.9),
+            transmission: 1.0,
+            roughness: 0.3,
+            ..Default::default()
+        }),
+        Box::new(Dielectric::tinted(1.5, Color::new(0.2, 0.6, 0.3), 0.5)),
+        Box::new(Coated::new(Box::new(Lambertian {
+            albedo: Color::new(0.6, 0.1, 0.1),
+        }))),
+        Box::new(ThinFilm {
+            base: Box::new(Lambertian {
+                albedo: Color::new(0.05, 0.05, 0.05),
+            }),
+            thickness: 400.0,
+            film_ior: 1.4,
+            base_ior: 1.5,
+        }),
+    ];
+    for (column, special) in special.into_iter().enumerate() {
+        let x = column as f64 - 2.0;
+        let roughness = column as f64 / 4.0;
+        let plastic = Principled::metallic_roughness(Color::new(0.2, 0.3, 0.8), 0.0, roughness);
+        let metal = Principled::metallic_roughness(Color::new(0.95, 0.7, 0.3), 1.0, roughness);
+        world.add(sphere(Point3::new(x, 0.4, 1.0), 0.4, plastic));
+        world.add(sphere(Point3::new(x, 0.4, 0.0), 0.4, metal));
+        world.add(Sphere {
+            center: Point3::new(x, 0.4, -1.0),
+            radius: 0.4,
+            mat: special,
+        });
+    }
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(16.0 / 9.0)
+        .image_width(600)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .environment(Gradient::default())
+        .projection(Projection::Perspective { vfov: 40.0 })
+        .center(Point3::new(0.0, 4.5, 5.0))
+        .look_at(Point3::new(0.0, 0.0, -0.2));
+    Scene { world, camera }
+}
+
+/// `material` on a unit sphere over a checkered floor, lit by the sun and
+/// sky.
+pub fn material_preview(material: Box<dyn Material>) -> Scene {
+    let floor = MixMaterial::textured(
+        Box::new(Lambertian {
+            albedo: Color::new(0.8, 0.8, 0.8),
+        }),
+        Box::new(Lambertian {
+            albedo: Color::new(0.1, 0.1, 0.1),
+        }),
+        Box::new(CheckerTexture::new(
+            0.4,
+            Color::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        )),
+    );
+
+    let mut world = HittableList::default();
+    // The floor sits halfway through a layer of checker cells, so rounding
+    // never flips a point on it into the next layer.
+    world.add(Quad::new(
+        Point3::new(-10.0, -1.0, 10.0),
+        Vec3::new(20.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -20.0),
+        Box::new(floor),
+    ));
+    world.add(Sphere {
+        center: Point3::new(0.0, 0.0, 0.0),
+        radius: 1.0,
+        mat: material,
+    });
+
+    let camera = CameraBuilder::new()
+        .aspect_ratio(1.0)
+        .image_width(400)
+        .samples_per_pixel(100)
+        .max_depth(50)
+        .environment(SunSky {
+            elevation: 35.0,
+            azimuth: 140.0,
+            ..Default::default()
+        })
+        .projection(Projection::Perspective { vfov: 35.0 })
+        .center(Point3::new(0.0, 1.8, 6.5))
+        .look_at(Point3::new(0.0, -0.2, 0.0));
+    Scene { world, camera }
+}
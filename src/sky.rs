@@ -0,0 +1,191 @@
+use std::f64::consts::PI;
+
+use crate::{color::Color, environment::Environment, onb::Onb, spectrum, vec3::Vec3};
+
+/// Angular radius of the sun seen from the ground, in degrees.
+pub const SUN_RADIUS: f64 = 0.2666;
+
+/// Luminance of the sun above the atmosphere, in kcd/m².
+const SUN_LUMINANCE: f64 = 1.6e6;
+
+/// Daylight from the Preetham, Shirley and Smits analytic sky model ("A
+/// Practical Analytic Model for Daylight", 1999), plus the sun disk.
+///
+/// Angles are in degrees. An azimuth of zero puts the sun straight ahead
+/// along -Z, positive values turn it towards +X. Directions below the
+/// horizon see the sky at the horizon.
+#[derive(Clone, Copy, Debug)]
+pub struct SunSky {
+    pub elevation: f64,
+    pub azimuth: f64,
+    /// Haziness of the atmosphere, from 2 (very clear) to about 10.
+    pub turbidity: f64,
+    /// Scale from the model's kcd/m² to scene radiance.
+    pub intensity: f64,
+    /// Angular radius of the sun disk, in degrees. The sun's total power
+    /// stays the same when it is resized.
+    pub sun_radius: f64,
+}
+
+impl Default for SunSky {
+    fn default() -> Self {
+        Self {
+            elevation: 45.0,
+            azimuth: 0.0,
+            turbidity: 3.0,
+            intensity: 0.05,
+            sun_radius: SUN_RADIUS,
+        }
+    }
+}
+
+impl SunSky {
+    pub fn sun_direction(&self) -> Vec3 {
+        let (elevation, azimuth) = (self.elevation.to_radians(), self.azimuth.to_radians());
+        Vec3::new(
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.cos(),
+        )
+    }
+
+    fn sun_cos_radius(&self) -> f64 {
+        self.sun_radius.to_radians().cos()
+    }
+
+    fn sun_pdf(&self) -> f64 {
+        1.0 / (2.0 * PI * (1.0 - self.sun_cos_radius()))
+    }
+
+    /// Sunlight after passing through the atmosphere: Rayleigh scattering
+    /// and Ångström's haze formula, evaluated at a red, a green and a blue
+    /// wavelength over the relative air mass of the sun's elevation.
+    pub fn sun_radiance(&self) -> Color {
+        let theta = (90.0 - self.elevation.clamp(0.0, 90.0)).min(93.0);
+        let air_mass = 1.0 / (theta.to_radians().cos() + 0.15 * (93.885 - theta).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let transmittance = |lambda_um: f64| {
+            let rayleigh = 0.008735 * lambda_um.powf(-4.08);
+            let haze = beta * lambda_um.powf(-1.3);
+            (-(rayleigh + haze) * air_mass).exp()
+        };
+        let color = Color::new(
+            transmittance(0.68),
+            transmittance(0.55),
+            transmittance(0.44),
+        );
+
+        // Keep the power constant however large the disk is made.
+        let size = (SUN_RADIUS.to_radians().sin() / self.sun_radius.to_radians().sin()).powi(2);
+        color * (SUN_LUMINANCE * size * self.intensity)
+    }
+
+    fn sky(&self, direction: &Vec3) -> Color {
+        let t = self.turbidity;
+        let theta_s = (90.0 - self.elevation.clamp(0.0, 90.0)).to_radians();
+        // Keep the view angle off the horizon, where the Perez function
+        // blows up.
+        let cos_theta = direction.y.max(0.01);
+        let cos_gamma = direction.dot(&self.sun_direction()).clamp(-1.0, 1.0);
+
+        let coefficients_y = [
+            0.1787 * t - 1.4630,
+            -0.3554 * t + 0.4275,
+            -0.0227 * t + 5.3251,
+            0.1206 * t - 2.5771,
+            -0.0670 * t + 0.3703,
+        ];
+        let coefficients_x = [
+            -0.0193 * t - 0.2592,
+            -0.0665 * t + 0.0008,
+            -0.0004 * t + 0.2125,
+            -0.0641 * t - 0.8989,
+            -0.0033 * t + 0.0452,
+        ];
+        let coefficients_chroma_y = [
+            -0.0167 * t - 0.2608,
+            -0.0950 * t + 0.0092,
+            -0.0079 * t + 0.2102,
+            -0.0441 * t - 1.6537,
+            -0.0109 * t + 0.0529,
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_y = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192).max(0.0);
+        let zenith_x = zenith_chromaticity(
+            t,
+            theta_s,
+            [
+                [0.00166, -0.00375, 0.00209, 0.0],
+                [-0.02903, 0.06377, -0.03202, 0.00394],
+                [0.11693, -0.21196, 0.06052, 0.25886],
+            ],
+        );
+        let zenith_chroma_y = zenith_chromaticity(
+            t,
+            theta_s,
+            [
+                [0.00275, -0.00610, 0.00317, 0.0],
+                [-0.04214, 0.08970, -0.04153, 0.00516],
+                [0.15346, -0.26756, 0.06670, 0.26688],
+            ],
+        );
+
+        let relative = |c: &[f64; 5]| perez(c, cos_theta, cos_gamma) / perez(c, 1.0, theta_s.cos());
+        let luminance = zenith_y * relative(&coefficients_y);
+        let x = zenith_x * relative(&coefficients_x);
+        let y = zenith_chroma_y * relative(&coefficients_chroma_y);
+
+        let xyz = Color::new(x / y * luminance, luminance, (1.0 - x - y) / y * luminance);
+        let rgb = spectrum::xyz_to_linear_srgb(xyz);
+        Color::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0)) * self.intensity
+    }
+}
+
+impl Environment for SunSky {
+    /// Radiance of the sky (and of the sun, inside its disk) along `direction`.
+    fn value(&self, direction: &Vec3) -> Color {
+        let direction = direction.unit_vector();
+        let mut color = self.sky(&direction);
+        if direction.dot(&self.sun_direction()) >= self.sun_cos_radius() {
+            color += self.sun_radiance();
+        }
+        color
+    }
+
+    /// Draws a direction uniformly over the sun disk, with its solid angle
+    /// density. The sky itself is left to the materials.
+    fn sample(&self, (u1, u2): (f64, f64)) -> Option<(Vec3, f64)> {
+        let cos_max = self.sun_cos_radius();
+        let cos_theta = 1.0 - u1 * (1.0 - cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * u2;
+        let local = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta);
+        let direction = Onb::new(&self.sun_direction()).transform(&local);
+        Some((direction, self.sun_pdf()))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        if direction.unit_vector().dot(&self.sun_direction()) >= self.sun_cos_radius() {
+            self.sun_pdf()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Perez et al.'s sky luminance distribution.
+fn perez(c: &[f64; 5], cos_theta: f64, cos_gamma: f64) -> f64 {
+    let gamma = cos_gamma.acos();
+    (1.0 + c[0] * (c[1] / cos_theta).exp())
+        * (1.0 + c[2] * (c[3] * gamma).exp() + c[4] * cos_gamma * cos_gamma)
+}
+
+/// `[T², T, 1] · m · [θs³, θs², θs, 1]`
+fn zenith_chromaticity(t: f64, theta_s: f64, m: [[f64; 4]; 3]) -> f64 {
+    let turbidity = [t * t, t, 1.0];
+    let angle = [theta_s.powi(3), theta_s.powi(2), theta_s, 1.0];
+    (0..3)
+        .map(|i| turbidity[i] * (0..4).map(|j| m[i][j] * angle[j]).sum::<f64>())
+        .sum()
+}
@@ -0,0 +1,85 @@
+use std::sync::OnceLock;
+
+use crate::color::Color;
+
+/// Shortest wavelength traced in spectral mode, in nanometers.
+pub const LAMBDA_MIN: f64 = 380.0;
+/// Longest wavelength traced in spectral mode, in nanometers.
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// Maps a uniform sample in [0, 1) to a wavelength in the visible range.
+/// The matching pdf is `1 / (LAMBDA_MAX - LAMBDA_MIN)`.
+pub fn sample_wavelength(u: f64) -> f64 {
+    LAMBDA_MIN + u * (LAMBDA_MAX - LAMBDA_MIN)
+}
+
+fn piecewise_gaussian(x: f64, mu: f64, sigma_lo: f64, sigma_hi: f64) -> f64 {
+    let sigma = if x < mu { sigma_lo } else { sigma_hi };
+    let t = (x - mu) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+/// CIE 1931 2° color matching functions, using the multi-lobe fit from
+/// Wyman, Sloan and Shirley, "Simple Analytic Approximations to the CIE XYZ
+/// Color Matching Functions" (2013).
+pub fn cie_xyz(lambda: f64) -> Color {
+    let x = 1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8);
+
+    Color::new(x, y, z)
+}
+
+/// CIE XYZ to linear sRGB (D65).
+pub fn xyz_to_linear_srgb(xyz: Color) -> Color {
+    Color::new(
+        3.2406 * xyz.x - 1.5372 * xyz.y - 0.4986 * xyz.z,
+        -0.9689 * xyz.x + 1.8758 * xyz.y + 0.0415 * xyz.z,
+        0.0557 * xyz.x - 0.2040 * xyz.y + 1.0570 * xyz.z,
+    )
+}
+
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Evaluates a smooth reflectance spectrum for an RGB albedo at `lambda`.
+///
+/// The three basis curves form a partition of unity, so greys stay perfectly
+/// flat and any albedo in [0, 1] stays a valid reflectance. Round-tripping the
+/// primaries through `spectral_to_rgb` lands within a few percent of the input.
+pub fn rgb_to_spectrum(rgb: Color, lambda: f64) -> f64 {
+    let blue = 1.0 - smoothstep(460.0, 520.0, lambda);
+    let red = smoothstep(560.0, 620.0, lambda);
+    let green = 1.0 - blue - red;
+
+    rgb.x * red + rgb.y * green + rgb.z * blue
+}
+
+/// Linear sRGB response to a flat (equal energy) spectrum of value one.
+fn equal_energy_rgb() -> Color {
+    static WHITE: OnceLock<Color> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let steps = (LAMBDA_MAX - LAMBDA_MIN) as usize;
+        let mut xyz = Color::default();
+        for i in 0..steps {
+            xyz += cie_xyz(LAMBDA_MIN + i as f64 + 0.5);
+        }
+        xyz_to_linear_srgb(xyz)
+    })
+}
+
+/// Converts a radiance sample carried at a single, uniformly sampled
+/// wavelength into its linear sRGB contribution. Averaging these over many
+/// wavelengths converges to the color of the full spectrum, normalized so
+/// that a flat spectrum of one is white.
+pub fn spectral_to_rgb(radiance: f64, lambda: f64) -> Color {
+    let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+    let rgb = xyz_to_linear_srgb(cie_xyz(lambda) * (radiance / pdf));
+    rgb / equal_energy_rgb()
+}
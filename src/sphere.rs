@@ -1,8 +1,8 @@
 use crate::hittable::{HitRecord, Hittable};
 use crate::interval::Interval;
 use crate::material::Material;
-use crate::Point3;
-use crate::Ray;
+use crate::ray::Point3;
+use crate::ray::Ray;
 
 pub struct Sphere {
     pub center: Point3,
@@ -10,6 +10,20 @@ pub struct Sphere {
     pub mat: Box<dyn Material>,
 }
 
+impl Sphere {
+    /// Spherical (u, v) in [0, 1] for a point `p` on the unit sphere, with
+    /// u going around the Y axis from X = -1 and v going from Y = -1 to 1.
+    fn get_sphere_uv(p: &Point3) -> (f64, f64) {
+        let theta = (-p.y).acos();
+        let phi = (-p.z).atan2(p.x) + std::f64::consts::PI;
+
+        (
+            phi / (2.0 * std::f64::consts::PI),
+            theta / std::f64::consts::PI,
+        )
+    }
+}
+
 impl Hittable for Sphere {
     fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
         let oc = self.center - ray.origin;
@@ -39,6 +53,7 @@ impl Hittable for Sphere {
         rec.p = ray.at(rec.t);
         let outward_normal = (rec.p - self.center) / self.radius;
         rec.set_face_normal(ray, &outward_normal);
+        (rec.u, rec.v) = Self::get_sphere_uv(&outward_normal);
         rec.mat = self.mat.clone();
 
         Some(rec)
@@ -0,0 +1,60 @@
+use dyn_clone::DynClone;
+
+use crate::{color::Color, ray::Point3};
+
+pub trait Texture: DynClone {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+dyn_clone::clone_trait_object!(Texture);
+
+#[derive(Clone, Copy, Default)]
+pub struct SolidColor {
+    pub albedo: Color,
+}
+
+impl SolidColor {
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Self {
+            albedo: Color::new(r, g, b),
+        }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.albedo
+    }
+}
+
+/// 3D checker pattern with cubes of side `scale`, alternating `even`/`odd`.
+#[derive(Clone)]
+pub struct CheckerTexture {
+    pub scale: f64,
+    pub even: Box<dyn Texture>,
+    pub odd: Box<dyn Texture>,
+}
+
+impl CheckerTexture {
+    pub fn new(scale: f64, even: Color, odd: Color) -> Self {
+        Self {
+            scale,
+            even: Box::new(SolidColor { albedo: even }),
+            odd: Box::new(SolidColor { albedo: odd }),
+        }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let x = (p.x / self.scale).floor() as i64;
+        let y = (p.y / self.scale).floor() as i64;
+        let z = (p.z / self.scale).floor() as i64;
+
+        if (x + y + z) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
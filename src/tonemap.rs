@@ -0,0 +1,140 @@
+use crate::color::Color;
+
+/// Maps scene-referred linear radiance into the displayable [0, 1] range.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ToneMapper {
+    /// Hard clip at 1.0.
+    #[default]
+    Clamp,
+    /// `x / (1 + x)` per channel.
+    Reinhard,
+    /// Reinhard that maps `white` to 1.0 instead of infinity.
+    ReinhardExtended { white: f64 },
+    /// Stephen Hill's fit of the ACES RRT + sRGB ODT.
+    AcesFilmic,
+    /// Troy Sobotka's AgX base look, using the polynomial sigmoid fit.
+    AgX,
+}
+
+/// Encodes display-referred linear values for an 8-bit image.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum TransferFunction {
+    /// Square root, what the renderer used before `Srgb`.
+    Gamma2,
+    /// The piecewise sRGB curve from IEC 61966-2-1.
+    #[default]
+    Srgb,
+    Linear,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DisplayTransform {
+    /// Exposure adjustment in stops, applied before tone mapping.
+    pub exposure: f64,
+    pub tone_mapper: ToneMapper,
+    pub transfer: TransferFunction,
+}
+
+impl DisplayTransform {
+    /// Linear radiance to an encoded display value in [0, 1].
+    pub fn apply(&self, color: Color) -> Color {
+        let exposed = color * 2f64.powf(self.exposure);
+        let mapped = self.tone_mapper.apply(exposed);
+        self.transfer.encode(mapped)
+    }
+}
+
+impl ToneMapper {
+    pub fn apply(&self, color: Color) -> Color {
+        let color = map_channels(color, |c| c.max(0.0));
+        match *self {
+            ToneMapper::Clamp => map_channels(color, |c| c.min(1.0)),
+            ToneMapper::Reinhard => map_channels(color, |c| c / (1.0 + c)),
+            ToneMapper::ReinhardExtended { white } => {
+                let white2 = white * white;
+                map_channels(color, |c| (c * (1.0 + c / white2) / (1.0 + c)).min(1.0))
+            }
+            ToneMapper::AcesFilmic => aces_filmic(color),
+            ToneMapper::AgX => agx(color),
+        }
+    }
+}
+
+impl TransferFunction {
+    pub fn encode(&self, color: Color) -> Color {
+        match self {
+            TransferFunction::Gamma2 => map_channels(color, f64::sqrt),
+            TransferFunction::Srgb => map_channels(color, |c| {
+                if c <= 0.003_130_8 {
+                    12.92 * c
+                } else {
+                    1.055 * c.powf(1.0 / 2.4) - 0.055
+                }
+            }),
+            TransferFunction::Linear => color,
+        }
+    }
+}
+
+fn map_channels(color: Color, f: impl Fn(f64) -> f64) -> Color {
+    Color::new(f(color.x), f(color.y), f(color.z))
+}
+
+fn mul_matrix(m: &[[f64; 3]; 3], c: Color) -> Color {
+    Color::new(
+        m[0][0] * c.x + m[0][1] * c.y + m[0][2] * c.z,
+        m[1][0] * c.x + m[1][1] * c.y + m[1][2] * c.z,
+        m[2][0] * c.x + m[2][1] * c.y + m[2][2] * c.z,
+    )
+}
+
+fn aces_filmic(color: Color) -> Color {
+    const INPUT: [[f64; 3]; 3] = [
+        [0.59719, 0.35458, 0.04823],
+        [0.07600, 0.90834, 0.01566],
+        [0.02840, 0.13383, 0.83777],
+    ];
+    const OUTPUT: [[f64; 3]; 3] = [
+        [1.60475, -0.53108, -0.07367],
+        [-0.10208, 1.10813, -0.00605],
+        [-0.00327, -0.07276, 1.07602],
+    ];
+
+    let v = mul_matrix(&INPUT, color);
+    let rrt_odt = map_channels(v, |v| {
+        let a = v * (v + 0.024_578_6) - 0.000_090_537;
+        let b = v * (0.983_729 * v + 0.432_951) + 0.238_081;
+        a / b
+    });
+    map_channels(mul_matrix(&OUTPUT, rrt_odt), |c| c.clamp(0.0, 1.0))
+}
+
+fn agx(color: Color) -> Color {
+    const INSET: [[f64; 3]; 3] = [
+        [0.842_479_062, 0.078_433_600, 0.079_223_745],
+        [0.042_328_242, 0.878_468_636, 0.079_166_127],
+        [0.042_375_655, 0.078_433_600, 0.879_142_974],
+    ];
+    const OUTSET: [[f64; 3]; 3] = [
+        [1.196_879_005, -0.098_020_881, -0.099_029_744],
+        [-0.052_896_852, 1.151_903_130, -0.098_961_177],
+        [-0.052_971_636, -0.098_043_450, 1.151_073_673],
+    ];
+    const MIN_EV: f64 = -12.473_93;
+    const MAX_EV: f64 = 4.026_069;
+
+    let v = mul_matrix(&INSET, color);
+    let encoded = map_channels(v, |c| {
+        let ev = c.max(1e-10).log2().clamp(MIN_EV, MAX_EV);
+        let x = (ev - MIN_EV) / (MAX_EV - MIN_EV);
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    });
+    // The sigmoid's output is display encoded with a 2.2 gamma; undo it so
+    // every operator hands linear values to the transfer function.
+    map_channels(mul_matrix(&OUTSET, encoded), |c| {
+        c.clamp(0.0, 1.0).powf(2.2)
+    })
+}
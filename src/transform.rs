@@ -0,0 +1,101 @@
+use crate::{
+    hittable::{HitRecord, Hittable},
+    interval::Interval,
+    ray::{Point3, Ray},
+    vec3::Vec3,
+};
+
+/// Placement of an object: scaled uniformly about its origin, rotated about
+/// X, then Y, then Z (in degrees), then moved by `translation`. A negative
+/// scale mirrors the object through its origin.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub scale: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::default(),
+            rotation: Vec3::default(),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    /// The rotation as the images of the X, Y and Z axes.
+    fn axes(&self) -> [Vec3; 3] {
+        let (sx, cx) = self.rotation.x.to_radians().sin_cos();
+        let (sy, cy) = self.rotation.y.to_radians().sin_cos();
+        let (sz, cz) = self.rotation.z.to_radians().sin_cos();
+        let rotate = |v: Vec3| {
+            let v = Vec3::new(v.x, cx * v.y - sx * v.z, sx * v.y + cx * v.z);
+            let v = Vec3::new(cy * v.x + sy * v.z, v.y, -sy * v.x + cy * v.z);
+            Vec3::new(cz * v.x - sz * v.y, sz * v.x + cz * v.y, v.z)
+        };
+        [
+            rotate(Vec3::new(1.0, 0.0, 0.0)),
+            rotate(Vec3::new(0.0, 1.0, 0.0)),
+            rotate(Vec3::new(0.0, 0.0, 1.0)),
+        ]
+    }
+
+    /// Where the object's point `p` ends up in the scene.
+    pub fn point(&self, p: Point3) -> Point3 {
+        let [x, y, z] = self.axes();
+        (x * p.x + y * p.y + z * p.z) * self.scale + self.translation
+    }
+}
+
+/// An object placed in the scene by a `Transform`.
+pub struct Transformed {
+    object: Box<dyn Hittable>,
+    translation: Vec3,
+    scale: f64,
+    axes: [Vec3; 3],
+}
+
+impl Transformed {
+    /// Panics unless the transform's scale is nonzero and finite.
+    pub fn new(object: impl Hittable + 'static, transform: Transform) -> Self {
+        assert!(
+            transform.scale != 0.0 && transform.scale.is_finite(),
+            "transform scale must be nonzero and finite, got {}",
+            transform.scale
+        );
+        Self {
+            object: Box::new(object),
+            translation: transform.translation,
+            scale: transform.scale,
+            axes: transform.axes(),
+        }
+    }
+
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        let [x, y, z] = self.axes;
+        Vec3::new(x.dot(&v), y.dot(&v), z.dot(&v)) / self.scale
+    }
+}
+
+impl Hittable for Transformed {
+    /// Hits the object with the ray taken into its own space. The direction
+    /// isn't normalized there, so the ray parameter carries over unchanged.
+    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let local = Ray {
+            origin: self.to_object(ray.origin - self.translation),
+            direction: self.to_object(ray.direction),
+            wavelength: ray.wavelength,
+        };
+        let mut hit = self.object.hit(&local, ray_t)?;
+
+        // Mirroring flips the local ray along with the object, so the hit
+        // still faces the same way, but its normal has to be flipped back.
+        let [x, y, z] = self.axes;
+        hit.p = ray.at(hit.t);
+        hit.normal = (x * hit.normal.x + y * hit.normal.y + z * hit.normal.z) * self.scale.signum();
+        Some(hit)
+    }
+}
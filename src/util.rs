@@ -11,5 +11,5 @@ pub fn random_f64() -> f64 {
 
 #[inline]
 pub fn random_f64_interval(min: f64, max: f64) -> f64 {
-    return min + (max - min) * random_f64();
+    min + (max - min) * random_f64()
 }
\ No newline at end of file
@@ -19,6 +19,15 @@ impl Vec3 {
         self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
     }
 
+    #[inline]
+    pub fn cross(&self, rhs: &Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+
     #[inline]
     pub fn length(&self) -> f64 {
         self.length_squared().sqrt()
@@ -45,13 +54,22 @@ impl Vec3 {
         }
     }
 
+    /// Maps a point of the unit square uniformly onto the unit sphere.
+    #[inline]
+    pub fn sample_unit_vector((u1, u2): (f64, f64)) -> Vec3 {
+        let z = 1.0 - 2.0 * u1;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * std::f64::consts::PI * u2;
+        Vec3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
     #[inline]
     pub fn random_on_hemisphere(normal: &Vec3) -> Vec3 {
         let on_unit_sphere = Self::random_unit_vector();
         if on_unit_sphere.dot(normal) > 0.0 {
-            return on_unit_sphere;
+            on_unit_sphere
         } else {
-            return -on_unit_sphere;
+            -on_unit_sphere
         }
     }
 
@@ -82,7 +100,7 @@ impl Vec3 {
 
     #[inline]
     pub fn refract(uv: &Vec3, n: &Vec3, etai_over_etat: f64) -> Vec3 {
-        let cos_theta = (-*uv).dot(&n).min(1.0);
+        let cos_theta = (-*uv).dot(n).min(1.0);
         let r_out_perp = (*uv + *n * cos_theta) * etai_over_etat;
         let r_out_parallel = *n * -(1.0 - r_out_perp.length_squared()).abs().sqrt();
         r_out_perp + r_out_parallel
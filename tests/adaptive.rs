@@ -0,0 +1,75 @@
+mod common;
+
+use common::{camera, scene};
+use raytracing_in_one_weekend::{
+    aov::AovSelection, camera::AdaptiveSampling, color::Color, environment::Constant, output,
+};
+
+#[test]
+fn samples_go_where_the_noise_is() {
+    let (_, aovs) = camera(1)
+        .environment(Constant {
+            color: Color::new(0.6, 0.7, 1.0),
+        })
+        .adaptive_sampling(AdaptiveSampling {
+            min_samples: 8,
+            max_samples: 64,
+            noise_threshold: 1e-4,
+        })
+        .aovs(AovSelection {
+            sample_count: true,
+            ..Default::default()
+        })
+        .build()
+        .render_with_aovs(&scene());
+    let counts = aovs.sample_count.unwrap();
+    let count = |x, y| counts.get_pixel(x, y).0[0];
+
+    // The sky is the same in every direction, so it converges right away.
+    // The diffuse ground below never gets under such a low threshold.
+    let (sky, ground) = ((16, 0), (16, 17));
+    assert_eq!(count(sky.0, sky.1), 8.0);
+    assert_eq!(count(ground.0, ground.1), 64.0);
+    assert!(counts.pixels().all(|p| (8.0..=64.0).contains(&p.0[0])));
+
+    // The heatmap runs from black for the fewest samples to white for the
+    // most, and gets brighter with the count.
+    let heatmap = output::sample_heatmap(&counts);
+    assert_eq!(heatmap.get_pixel(sky.0, sky.1).0, [0, 0, 0]);
+    assert_eq!(heatmap.get_pixel(ground.0, ground.1).0, [255, 255, 255]);
+    let brightness = |x, y| {
+        heatmap
+            .get_pixel(x, y)
+            .0
+            .iter()
+            .map(|&c| c as u32)
+            .sum::<u32>()
+    };
+    for (x, y, _) in counts.enumerate_pixels() {
+        if count(x, y) < count(ground.0, ground.1) {
+            assert!(brightness(x, y) < brightness(ground.0, ground.1));
+        }
+        if count(x, y) > count(sky.0, sky.1) {
+            assert!(brightness(x, y) > brightness(sky.0, sky.1));
+        }
+    }
+}
+
+#[test]
+fn loose_threshold_stops_everything_at_min_samples() {
+    let (_, aovs) = camera(1)
+        .adaptive_sampling(AdaptiveSampling {
+            min_samples: 4,
+            max_samples: 256,
+            noise_threshold: 1.0,
+        })
+        .aovs(AovSelection {
+            sample_count: true,
+            ..Default::default()
+        })
+        .build()
+        .render_with_aovs(&scene());
+
+    let counts = aovs.sample_count.unwrap();
+    assert!(counts.pixels().all(|p| p.0[0] == 4.0));
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use std::{f64::consts::PI, ops::ControlFlow};
+
+use common::transparent_camera;
+use image::{Rgba, Rgba32FImage};
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::Constant,
+    hittable_list::HittableList,
+    material::{Holdout, Lambertian, Material, ShadowCatcher},
+    output::{self, AlphaMode},
+    ray::Point3,
+    sphere::Sphere,
+    tonemap::{DisplayTransform, TransferFunction},
+};
+
+fn ground(mat: impl Material + 'static) -> Sphere {
+    Sphere {
+        center: Point3::new(0.0, -100.5, -1.0),
+        radius: 100.0,
+        mat: Box::new(mat),
+    }
+}
+
+fn ball(mat: impl Material + 'static) -> Sphere {
+    Sphere {
+        center: Point3::new(0.0, 0.0, -1.0),
+        radius: 0.5,
+        mat: Box::new(mat),
+    }
+}
+
+fn grey() -> Lambertian {
+    Lambertian {
+        albedo: Color::new(0.5, 0.5, 0.5),
+    }
+}
+
+fn catcher() -> ShadowCatcher {
+    ShadowCatcher {
+        albedo: Color::new(0.5, 0.5, 0.5),
+    }
+}
+
+fn white() -> Constant {
+    Constant {
+        color: Color::new(1.0, 1.0, 1.0),
+    }
+}
+
+#[test]
+fn alpha_is_the_fraction_of_samples_that_hit_geometry() {
+    let mut world = HittableList::default();
+    world.add(ball(grey()));
+    let image = transparent_camera(32, 16).build().render(&world);
+
+    // The ball's silhouette on the 2x2 viewport at distance 1 is a disk of
+    // radius tan(30°).
+    let expected = PI * (PI / 6.0).tan().powi(2) / 4.0;
+    let coverage: f64 = image.pixels().map(|p| p.0[3] as f64).sum::<f64>() / (32.0 * 32.0);
+    assert!(
+        (coverage - expected).abs() < 0.01 * expected,
+        "coverage {coverage}, expected {expected}"
+    );
+    assert!(image.pixels().any(|p| p.0[3] > 0.0 && p.0[3] < 1.0));
+}
+
+#[test]
+fn holdout_cuts_a_hole_in_an_opaque_render() {
+    let mut world = HittableList::default();
+    world.add(ground(grey()));
+    world.add(ball(Holdout));
+    let image = CameraBuilder::new()
+        .image_width(32)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(4)
+        .environment(white())
+        .build()
+        .render(&world);
+
+    assert_eq!(image.get_pixel(16, 16).0, [0.0; 4]);
+    assert_eq!(image.get_pixel(0, 0).0[3], 1.0);
+    assert_eq!(image.get_pixel(0, 31).0[3], 1.0);
+}
+
+#[test]
+fn shadow_catcher_on_its_own_is_invisible() {
+    let mut world = HittableList::default();
+    world.add(ground(catcher()));
+    let image = transparent_camera(32, 4).build().render(&world);
+
+    for pixel in image.pixels() {
+        assert!(pixel.0.iter().all(|&c| c.abs() < 1e-6), "{pixel:?}");
+    }
+}
+
+#[test]
+fn shadow_catcher_keeps_only_the_shadow() {
+    let mut world = HittableList::default();
+    world.add(ground(catcher()));
+    world.add(ball(grey()));
+    let image = transparent_camera(32, 64).build().render(&world);
+
+    // Ground just in front of the ball, and far off to the side.
+    let contact = image.get_pixel(16, 26).0;
+    let open = image.get_pixel(1, 16).0;
+    assert_eq!(&contact[..3], &[0.0; 3]);
+    assert!(contact[3] > 0.5, "{contact:?}");
+    assert!(open[3] < 0.05, "{open:?}");
+    assert_eq!(image.get_pixel(16, 16).0[3], 1.0);
+}
+
+#[test]
+fn shadows_survive_a_checkpoint() {
+    let mut world = HittableList::default();
+    world.add(ground(catcher()));
+    world.add(ball(grey()));
+    let path = std::env::temp_dir().join("alpha_test_shadow.ckpt");
+    let _ = std::fs::remove_file(&path);
+
+    let uninterrupted = transparent_camera(32, 8)
+        .samples_per_pass(4)
+        .build()
+        .render(&world);
+    transparent_camera(32, 8)
+        .samples_per_pass(4)
+        .checkpoint(&path)
+        .build()
+        .render_progressive(&world, |_| ControlFlow::Break(()));
+    let resumed = transparent_camera(32, 8)
+        .samples_per_pass(4)
+        .checkpoint(&path)
+        .build()
+        .render(&world);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(resumed, uninterrupted);
+}
+
+#[test]
+fn straight_and_premultiplied_output() {
+    let image = Rgba32FImage::from_pixel(1, 1, Rgba([0.25, 0.125, 0.0, 0.5]));
+    let display = DisplayTransform {
+        transfer: TransferFunction::Linear,
+        ..Default::default()
+    };
+
+    let straight = output::to_rgba8(&image, &display, AlphaMode::Straight);
+    let premultiplied = output::to_rgba8(&image, &display, AlphaMode::Premultiplied);
+    assert_eq!(
+        output::to_rgba8(&image, &display, AlphaMode::Auto),
+        straight
+    );
+    assert_eq!(straight.get_pixel(0, 0).0, [128, 64, 0, 128]);
+    assert_eq!(premultiplied.get_pixel(0, 0).0, [64, 32, 0, 128]);
+
+    assert_eq!(
+        output::unpremultiply(&image).get_pixel(0, 0).0,
+        [0.5, 0.25, 0.0, 0.5]
+    );
+}
+
+#[test]
+fn exr_alpha_mode() {
+    let image = Rgba32FImage::from_pixel(2, 2, Rgba([0.25, 0.125, 0.0, 0.5]));
+    let path = std::env::temp_dir().join("alpha_test_mode.exr");
+    let read_back = || image::open(&path).unwrap().into_rgba32f().get_pixel(0, 0).0;
+
+    output::save_linear(&image, AlphaMode::Auto, &path).unwrap();
+    assert_eq!(read_back(), [0.25, 0.125, 0.0, 0.5]);
+    output::save_linear(&image, AlphaMode::Straight, &path).unwrap();
+    assert_eq!(read_back(), [0.5, 0.25, 0.0, 0.5]);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,306 @@
+use std::{
+    fs::{self, File},
+    io::BufReader,
+};
+
+use image::{codecs::gif::GifDecoder, AnimationDecoder};
+use raytracing_in_one_weekend::{
+    animation::{Animation, CameraTracks, Interpolation, Track, TransformTracks},
+    camera::CameraBuilder,
+    color::Color,
+    hittable::Hittable,
+    hittable_list::HittableList,
+    interval::Interval,
+    material::Lambertian,
+    ray::{Point3, Ray},
+    sphere::Sphere,
+    transform::{Transform, Transformed},
+    vec3::Vec3,
+};
+
+fn unit_sphere() -> Sphere {
+    Sphere {
+        center: Point3::new(0.0, 0.0, 0.0),
+        radius: 1.0,
+        mat: Box::new(Lambertian::default()),
+    }
+}
+
+#[test]
+fn linear_tracks_hold_outside_their_keyframes() {
+    let track =
+        Track::new()
+            .key(2.0, 10.0, Interpolation::Linear)
+            .key(1.0, 0.0, Interpolation::Linear);
+
+    assert_eq!(track.at(0.0), Some(0.0));
+    assert_eq!(track.at(1.25), Some(2.5));
+    assert_eq!(track.at(3.0), Some(10.0));
+    assert_eq!(Track::<f64>::new().at(1.0), None);
+}
+
+#[test]
+fn bezier_eases_between_its_end_points() {
+    let track = Track::new()
+        .key(0.0, 0.0, Interpolation::Bezier(0.0, 1.0))
+        .key(1.0, 1.0, Interpolation::Linear);
+
+    assert_eq!(track.at(0.5), Some(0.5));
+    let early = track.at(0.1).unwrap();
+    let late = track.at(0.9).unwrap();
+    assert!(early < 0.1 && late > 0.9, "{early} {late}");
+    assert!((early + late - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn catmull_rom_passes_through_its_keyframes_smoothly() {
+    let track = Track::new()
+        .key(0.0, 0.0, Interpolation::CatmullRom)
+        .key(1.0, 2.0, Interpolation::CatmullRom)
+        .key(3.0, 1.0, Interpolation::CatmullRom)
+        .key(4.0, 5.0, Interpolation::CatmullRom);
+
+    for key in track.keys() {
+        assert!((track.at(key.time).unwrap() - key.value).abs() < 1e-12);
+    }
+    let h = 1e-6;
+    for t in [1.0, 3.0] {
+        let before = (track.at(t).unwrap() - track.at(t - h).unwrap()) / h;
+        let after = (track.at(t + h).unwrap() - track.at(t).unwrap()) / h;
+        assert!(
+            (before - after).abs() < 1e-4,
+            "kink at {t}: {before} {after}"
+        );
+    }
+
+    // Evenly spaced points on a line stay on it.
+    let line = Track::new()
+        .key(0.0, 0.0, Interpolation::CatmullRom)
+        .key(1.0, 1.0, Interpolation::CatmullRom)
+        .key(2.0, 2.0, Interpolation::CatmullRom);
+    assert!((line.at(0.3).unwrap() - 0.3).abs() < 1e-12);
+}
+
+#[test]
+fn transformed_objects_are_moved_turned_and_scaled() {
+    let transform = Transform {
+        translation: Vec3::new(0.0, 0.0, -5.0),
+        rotation: Vec3::new(0.0, 90.0, 0.0),
+        scale: 2.0,
+    };
+    let turned = transform.point(Vec3::new(1.0, 0.0, 0.0));
+    assert!((turned - Vec3::new(0.0, 0.0, -7.0)).length() < 1e-12);
+
+    let sphere = Transformed::new(unit_sphere(), transform);
+    let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -2.0));
+    let hit = sphere
+        .hit(&ray, &Interval::new(0.001, f64::INFINITY))
+        .unwrap();
+    assert!((hit.t - 1.5).abs() < 1e-12);
+    assert!((hit.p - Point3::new(0.0, 0.0, -3.0)).length() < 1e-12);
+    assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+    assert!(hit.front_face);
+}
+
+#[test]
+fn mirrored_objects_keep_their_outside() {
+    let mirrored = Transformed::new(
+        unit_sphere(),
+        Transform {
+            translation: Vec3::new(0.0, 0.0, -3.0),
+            scale: -1.0,
+            ..Default::default()
+        },
+    );
+    let interval = Interval::new(0.001, f64::INFINITY);
+
+    let outside = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+    let hit = mirrored.hit(&outside, &interval).unwrap();
+    assert!((hit.p - Point3::new(0.0, 0.0, -2.0)).length() < 1e-12);
+    assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+    assert!(hit.front_face);
+
+    let inside = Ray::new(Point3::new(0.0, 0.0, -3.0), Vec3::new(0.0, 0.0, 1.0));
+    let hit = mirrored.hit(&inside, &interval).unwrap();
+    assert!((hit.p - Point3::new(0.0, 0.0, -2.0)).length() < 1e-12);
+    assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-12);
+    assert!(!hit.front_face);
+}
+
+fn moving_sphere() -> TransformTracks {
+    TransformTracks {
+        translation: Track::new()
+            .key(0.0, Vec3::new(-1.0, 0.0, -3.0), Interpolation::Linear)
+            .key(1.0, Vec3::new(1.0, 0.0, -3.0), Interpolation::Linear),
+        scale: Track::new().key(0.0, 0.5, Interpolation::Linear),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sequences_resume_and_become_a_gif() {
+    let directory = std::env::temp_dir().join("animation_test_sequence");
+    let _ = fs::remove_dir_all(&directory);
+
+    let animation = Animation {
+        frame_count: 3,
+        frame_rate: 2.0,
+        seed: 7,
+        camera: CameraTracks {
+            vfov: Track::new().key(0.0, 90.0, Interpolation::Linear).key(
+                1.0,
+                60.0,
+                Interpolation::Linear,
+            ),
+            ..Default::default()
+        },
+    };
+    let camera = CameraBuilder::new()
+        .image_width(16)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(4);
+    let sphere = moving_sphere();
+    let scene = |time: f64| {
+        let mut world = HittableList::default();
+        world.add(Transformed::new(
+            Sphere {
+                mat: Box::new(Lambertian {
+                    albedo: Color::new(0.8, 0.2, 0.2),
+                }),
+                ..unit_sphere()
+            },
+            sphere.at(time),
+        ));
+        world
+    };
+
+    assert_eq!(animation.next_frame(&directory), 0);
+    animation
+        .render_sequence(&camera, scene, &directory, 0)
+        .unwrap();
+    assert_eq!(animation.next_frame(&directory), 3);
+    let frames: Vec<Vec<u8>> = (0..3)
+        .map(|frame| fs::read(Animation::frame_path(&directory, frame)).unwrap())
+        .collect();
+    assert_ne!(frames[0], frames[1]);
+    assert_ne!(frames[1], frames[2]);
+
+    // An interrupted render picks up where it stopped, with the same result.
+    for frame in 1..3 {
+        fs::remove_file(Animation::frame_path(&directory, frame)).unwrap();
+    }
+    let next = animation.next_frame(&directory);
+    assert_eq!(next, 1);
+    animation
+        .render_sequence(&camera, scene, &directory, next)
+        .unwrap();
+    for (frame, bytes) in frames.iter().enumerate() {
+        let path = Animation::frame_path(&directory, frame as u32);
+        assert_eq!(&fs::read(path).unwrap(), bytes, "frame {frame}");
+    }
+
+    let gif = directory.join("animation.gif");
+    animation.save_gif(&directory, &gif).unwrap();
+    let file = BufReader::new(File::open(&gif).unwrap());
+    let decoded = GifDecoder::new(file)
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+    assert_eq!(decoded.len(), 3);
+    assert_eq!(decoded[0].delay().numer_denom_ms(), (500, 1));
+    assert_eq!(decoded[0].buffer().dimensions(), (16, 16));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn frames_get_their_own_seeds() {
+    let animation = Animation {
+        frame_count: 2,
+        ..Default::default()
+    };
+    let camera = CameraBuilder::new()
+        .image_width(16)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(2);
+    let mut world = HittableList::default();
+    world.add(Transformed::new(unit_sphere(), moving_sphere().at(0.0)));
+
+    // Nothing moves, but the noise does.
+    let first = animation.camera(&camera, 0).render(&world);
+    let second = animation.camera(&camera, 1).render(&world);
+    let again = animation.camera(&camera, 1).render(&world);
+    assert_ne!(first, second);
+    assert_eq!(second, again);
+}
+
+#[test]
+fn nan_times_get_the_first_keyframe() {
+    let track =
+        Track::new()
+            .key(0.0, 1.0, Interpolation::Linear)
+            .key(1.0, 2.0, Interpolation::Linear);
+    assert_eq!(track.at(f64::NAN), Some(1.0));
+}
+
+#[test]
+#[should_panic(expected = "frame rate must be positive")]
+fn zero_frame_rate_is_rejected() {
+    let animation = Animation {
+        frame_rate: 0.0,
+        ..Default::default()
+    };
+    animation.time(1);
+}
+
+#[test]
+#[should_panic(expected = "transform scale must be nonzero")]
+fn zero_scale_is_rejected() {
+    Transformed::new(
+        unit_sphere(),
+        Transform {
+            scale: 0.0,
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn slow_gifs_keep_a_delay() {
+    let directory = std::env::temp_dir().join("animation_test_slow_gif");
+    let _ = fs::remove_dir_all(&directory);
+
+    // One frame every half hour, whose rate rounds to zero millihertz.
+    let animation = Animation {
+        frame_count: 1,
+        frame_rate: 1.0 / 1800.0,
+        ..Default::default()
+    };
+    let camera = CameraBuilder::new()
+        .image_width(4)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(1);
+    animation
+        .render_sequence(&camera, |_| HittableList::default(), &directory, 0)
+        .unwrap();
+    // Only the finished frame is left in the directory.
+    let names: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(names, ["frame_0000.png"]);
+
+    let gif = directory.join("animation.gif");
+    animation.save_gif(&directory, &gif).unwrap();
+    let file = BufReader::new(File::open(&gif).unwrap());
+    let decoded = GifDecoder::new(file)
+        .unwrap()
+        .into_frames()
+        .collect_frames()
+        .unwrap();
+    let (numer, denom) = decoded[0].delay().numer_denom_ms();
+    assert!(denom > 0 && numer / denom > 0, "{numer}/{denom} ms");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
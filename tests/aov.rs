@@ -0,0 +1,62 @@
+mod common;
+
+use common::{camera, scene};
+use image::Rgb32FImage;
+use raytracing_in_one_weekend::{aov::AovSelection, vec3::Vec3};
+
+fn at(buffer: &Option<Rgb32FImage>, x: u32, y: u32) -> Vec3 {
+    let [r, g, b] = buffer.as_ref().unwrap().get_pixel(x, y).0;
+    Vec3::new(r as f64, g as f64, b as f64)
+}
+
+fn close(a: Vec3, b: Vec3, tolerance: f64) -> bool {
+    (a - b).length() < tolerance
+}
+
+#[test]
+fn aovs_describe_the_first_hit() {
+    let (_, aovs) = camera(4)
+        .aovs(AovSelection::all())
+        .build()
+        .render_with_aovs(&scene());
+    // The image is 32 by 18: the small ball at the center, the ground at
+    // the bottom and the sky at the top.
+    let (center, ground, sky) = ((16, 9), (16, 17), (16, 0));
+
+    let depth = |(x, y)| at(&aovs.depth, x, y).x;
+    assert!((depth(center) - 0.5).abs() < 0.02, "{}", depth(center));
+    assert!(depth(ground) > depth(center) && depth(ground).is_finite());
+    assert_eq!(depth(sky), f64::INFINITY);
+
+    let normal = |(x, y)| at(&aovs.normal, x, y);
+    assert!(normal(center).z > 0.95, "{:?}", normal(center));
+    assert!(normal(ground).y > 0.99);
+    assert_eq!(normal(sky).length(), 0.0);
+
+    let albedo = |(x, y)| at(&aovs.albedo, x, y);
+    assert!(close(albedo(center), Vec3::new(0.1, 0.2, 0.5), 1e-6));
+    assert!(close(albedo(ground), Vec3::new(0.8, 0.8, 0.0), 1e-6));
+    assert_eq!(albedo(sky).length(), 0.0);
+
+    // Objects are numbered from 1 in the order they were added.
+    let id = |(x, y)| at(&aovs.object_id, x, y).x;
+    assert_eq!((id(center), id(ground), id(sky)), (2.0, 1.0, 0.0));
+
+    let position = at(&aovs.position, center.0, center.1);
+    assert!(
+        close(position, Vec3::new(0.0, 0.0, -0.5), 0.1),
+        "{position:?}"
+    );
+    assert_eq!(at(&aovs.sample_count, center.0, center.1).x, 4.0);
+}
+
+#[test]
+fn aovs_leave_the_beauty_pass_unchanged() {
+    let world = scene();
+    let (image, _) = camera(4)
+        .aovs(AovSelection::all())
+        .build()
+        .render_with_aovs(&world);
+
+    assert_eq!(image, camera(4).build().render(&world));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{mean, scene};
+use raytracing_in_one_weekend::{
+    aov::AovSelection,
+    camera::{CameraBuilder, CancellationToken},
+};
+
+/// Far more samples than any test will wait for.
+fn endless_camera() -> CameraBuilder {
+    common::camera(1_000_000).samples_per_pass(1)
+}
+
+#[test]
+fn time_budget_returns_a_normalized_image() {
+    let world = scene();
+    let start = Instant::now();
+    let image = endless_camera()
+        .time_budget(Duration::from_millis(300))
+        .build()
+        .render(&world);
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+    let reference = common::camera(64).build().render(&world);
+    let (got, expected) = (mean(&image), mean(&reference));
+    assert!(
+        (got - expected).abs() < 0.05 * expected,
+        "mean {got} after the time budget, expected about {expected}"
+    );
+}
+
+#[test]
+fn cancelling_from_another_thread_stops_the_render() {
+    let token = CancellationToken::new();
+    let canceller = {
+        let token = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            token.cancel();
+        })
+    };
+
+    let start = Instant::now();
+    let image = endless_camera()
+        .cancellation(token)
+        .build()
+        .render(&scene());
+    canceller.join().unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(image.pixels().flat_map(|p| p.0).all(f32::is_finite));
+    assert!(mean(&image) > 0.0);
+}
+
+#[test]
+fn render_cancelled_up_front_takes_no_samples() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let image = endless_camera()
+        .cancellation(token)
+        .build()
+        .render(&scene());
+
+    assert_eq!(mean(&image), 0.0);
+}
+
+/// Renders with only a stop condition set, and returns the fewest samples
+/// any pixel got.
+fn fewest_samples(camera: CameraBuilder) -> f32 {
+    let (_, aovs) = camera
+        .aovs(AovSelection {
+            sample_count: true,
+            ..Default::default()
+        })
+        .build()
+        .render_with_aovs(&scene());
+    let counts = aovs.sample_count.unwrap();
+    counts
+        .pixels()
+        .map(|p| p.0[0])
+        .fold(f32::INFINITY, f32::min)
+}
+
+#[test]
+fn time_budget_alone_samples_every_pixel() {
+    let camera = common::camera(1_000_000).time_budget(Duration::from_millis(200));
+    assert!(fewest_samples(camera) >= 1.0);
+}
+
+#[test]
+fn cancellation_alone_samples_every_pixel() {
+    let token = CancellationToken::new();
+    let canceller = {
+        let token = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            token.cancel();
+        })
+    };
+
+    let fewest = fewest_samples(common::camera(1_000_000).cancellation(token));
+    canceller.join().unwrap();
+    assert!(fewest >= 1.0);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use std::{ops::ControlFlow, path::PathBuf, time::Duration};
+
+use common::scene;
+use raytracing_in_one_weekend::{
+    aov::AovSelection,
+    camera::CameraBuilder,
+    color::Color,
+    environment::Constant,
+    filter::Filter,
+    lens::RealisticLens,
+    projection::{Projection, Stereo},
+    ray::Point3,
+    sampler::SamplerKind,
+    vec3::Vec3,
+};
+
+fn camera(samples_per_pixel: i32) -> CameraBuilder {
+    common::camera(samples_per_pixel)
+        .samples_per_pass(4)
+        .aovs(AovSelection {
+            normal: true,
+            sample_count: true,
+            ..Default::default()
+        })
+}
+
+fn checkpoint_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("checkpoint_test_{name}.ckpt"));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn resumed_render_matches_an_uninterrupted_one() {
+    let world = scene();
+    let path = checkpoint_path("resume");
+
+    // Kill the first run after one pass.
+    camera(12)
+        .checkpoint(&path)
+        .build()
+        .render_progressive(&world, |_| ControlFlow::Break(()));
+    assert!(path.exists());
+
+    let (resumed, resumed_aovs) = camera(12)
+        .checkpoint(&path)
+        .build()
+        .render_with_aovs(&world);
+    let (uninterrupted, aovs) = camera(12).build().render_with_aovs(&world);
+
+    assert_eq!(resumed, uninterrupted);
+    assert_eq!(resumed_aovs.normal, aovs.normal);
+    assert_eq!(resumed_aovs.sample_count, aovs.sample_count);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn finished_render_can_be_continued_with_more_samples() {
+    let world = scene();
+    let path = checkpoint_path("continue");
+
+    camera(8).checkpoint(&path).build().render(&world);
+    let continued = camera(16).checkpoint(&path).build().render(&world);
+
+    assert_eq!(continued, camera(16).build().render(&world));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn checkpoint_from_another_render_is_ignored() {
+    let world = scene();
+    let path = checkpoint_path("mismatch");
+
+    camera(8).seed(1).checkpoint(&path).build().render(&world);
+    let fresh = camera(8).seed(2).checkpoint(&path).build().render(&world);
+
+    assert_eq!(fresh, camera(8).seed(2).build().render(&world));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn checkpoint_settings_must_match_to_resume() {
+    let world = scene();
+    let changes: [fn(CameraBuilder) -> CameraBuilder; 5] = [
+        |camera| camera.sampler(SamplerKind::Sobol),
+        |camera| camera.filter(Filter::Tent { radius: 1.0 }),
+        |camera| camera.spectral(true),
+        |camera| camera.max_depth(4),
+        |camera| camera.samples_per_pass(3),
+    ];
+
+    for (index, change) in changes.into_iter().enumerate() {
+        let path = checkpoint_path(&format!("settings_{index}"));
+        camera(8).checkpoint(&path).build().render(&world);
+        let fresh = change(camera(8)).checkpoint(&path).build().render(&world);
+
+        assert_eq!(fresh, change(camera(8)).build().render(&world), "{index}");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn checkpoint_from_another_view_is_ignored() {
+    let world = scene();
+    let changes: [fn(CameraBuilder) -> CameraBuilder; 7] = [
+        |camera| camera.center(Point3::new(0.0, 0.2, 0.0)),
+        |camera| camera.look_at(Point3::new(0.3, 0.0, -1.0)),
+        |camera| camera.up(Vec3::new(0.3, 1.0, 0.0)),
+        |camera| camera.projection(Projection::Perspective { vfov: 60.0 }),
+        |camera| camera.lens(RealisticLens::default()),
+        |camera| camera.stereo(Stereo::default()),
+        |camera| {
+            camera.environment(Constant {
+                color: Color::new(1.0, 1.0, 1.0),
+            })
+        },
+    ];
+
+    for (index, change) in changes.into_iter().enumerate() {
+        let path = checkpoint_path(&format!("view_{index}"));
+        camera(8).checkpoint(&path).build().render(&world);
+        let fresh = change(camera(8)).checkpoint(&path).build().render(&world);
+
+        assert_eq!(fresh, change(camera(8)).build().render(&world), "{index}");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn stratified_render_is_not_continued_with_more_samples() {
+    let world = scene();
+    let path = checkpoint_path("stratified");
+    let stratified = |samples| camera(samples).sampler(SamplerKind::Stratified);
+
+    stratified(8).checkpoint(&path).build().render(&world);
+    let fresh = stratified(16).checkpoint(&path).build().render(&world);
+
+    assert_eq!(fresh, stratified(16).build().render(&world));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn checkpoint_is_written_during_a_single_pass_render() {
+    let world = scene();
+    let path = checkpoint_path("periodic");
+
+    // Without `samples_per_pass` this would be one pass of all 12 samples.
+    let mut passes = 0;
+    common::camera(12)
+        .checkpoint(&path)
+        .checkpoint_interval(Duration::ZERO)
+        .build()
+        .render_progressive(&world, |pass| {
+            assert_eq!(pass.samples_per_pixel, pass.index as i32);
+            assert_eq!(path.exists(), pass.index > 1);
+            passes += 1;
+            if pass.index == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+    assert_eq!(passes, 3);
+
+    let resumed = common::camera(12).checkpoint(&path).build().render(&world);
+    assert_eq!(resumed, common::camera(12).build().render(&world));
+    std::fs::remove_file(&path).unwrap();
+}
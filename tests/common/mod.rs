@@ -0,0 +1,89 @@
+//! Scenes, cameras and image metrics shared by the rendering tests. Not
+//! every test binary uses all of it.
+#![allow(dead_code)]
+
+use image::Rgba32FImage;
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::{Constant, Transparent},
+    hittable_list::HittableList,
+    material::Lambertian,
+    ray::Point3,
+    sphere::Sphere,
+};
+
+/// A small sphere resting on a large one.
+pub fn scene() -> HittableList {
+    let mut world = HittableList::default();
+    world.add(Sphere {
+        center: Point3::new(0.0, -100.5, -1.0),
+        radius: 100.0,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.8, 0.8, 0.0),
+        }),
+    });
+    world.add(Sphere {
+        center: Point3::new(0.0, 0.0, -1.0),
+        radius: 0.5,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.1, 0.2, 0.5),
+        }),
+    });
+    world
+}
+
+/// A grey ball of radius 0.5 on its own.
+pub fn ball_at(center: Point3) -> HittableList {
+    let mut world = HittableList::default();
+    world.add(Sphere {
+        center,
+        radius: 0.5,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        }),
+    });
+    world
+}
+
+/// A 32 pixel wide camera looking at `scene`.
+pub fn camera(samples_per_pixel: i32) -> CameraBuilder {
+    CameraBuilder::new()
+        .image_width(32)
+        .samples_per_pixel(samples_per_pixel)
+        .max_depth(8)
+}
+
+/// Square camera over a transparent, uniformly white environment.
+pub fn transparent_camera(image_width: u32, samples_per_pixel: i32) -> CameraBuilder {
+    CameraBuilder::new()
+        .image_width(image_width)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(samples_per_pixel)
+        .environment(Transparent::new(Constant {
+            color: Color::new(1.0, 1.0, 1.0),
+        }))
+}
+
+/// Mean of the color channels.
+pub fn mean(image: &Rgba32FImage) -> f64 {
+    let sum: f64 = image
+        .pixels()
+        .flat_map(|p| &p.0[..3])
+        .map(|&c| c as f64)
+        .sum();
+    sum / (image.width() * image.height() * 3) as f64
+}
+
+/// Root mean square error after a Reinhard curve, so a few bright outliers
+/// don't dominate the comparison.
+pub fn rmse(a: &Rgba32FImage, b: &Rgba32FImage) -> f64 {
+    let compress = |v: f32| v as f64 / (1.0 + v as f64);
+    let sum: f64 = a
+        .pixels()
+        .zip(b.pixels())
+        .flat_map(|(p, q)| (0..3).map(move |c| compress(p.0[c]) - compress(q.0[c])))
+        .map(|d| d * d)
+        .sum();
+    (sum / (a.width() * a.height() * 3) as f64).sqrt()
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{rmse, scene};
+use image::{Rgb, Rgb32FImage};
+use raytracing_in_one_weekend::{aov::AovSelection, camera::CameraBuilder, denoise::Denoiser};
+
+fn camera(samples_per_pixel: i32) -> CameraBuilder {
+    common::camera(samples_per_pixel).image_width(64)
+}
+
+#[test]
+fn denoising_a_noisy_render_moves_it_closer_to_the_reference() {
+    let world = scene();
+    let reference = camera(256).build().render(&world);
+
+    let (noisy, aovs) = camera(2)
+        .aovs(AovSelection {
+            albedo: true,
+            normal: true,
+            ..Default::default()
+        })
+        .build()
+        .render_with_aovs(&world);
+    let denoised = Denoiser::default().apply(&noisy, aovs.albedo.as_ref(), aovs.normal.as_ref());
+
+    let noisy_error = rmse(&noisy, &reference);
+    let denoised_error = rmse(&denoised, &reference);
+    assert!(
+        denoised_error < 0.7 * noisy_error,
+        "denoised error {denoised_error} is not clearly below noisy error {noisy_error}"
+    );
+}
+
+#[test]
+fn camera_denoiser_only_returns_the_requested_aovs() {
+    let (_, aovs) = camera(2)
+        .denoiser(Denoiser::default())
+        .build()
+        .render_with_aovs(&scene());
+
+    assert!(aovs.albedo.is_none());
+    assert!(aovs.normal.is_none());
+}
+
+#[test]
+fn zero_strength_leaves_the_image_untouched() {
+    let image = Rgb32FImage::from_fn(8, 8, |x, y| Rgb([x as f32, y as f32, 0.5]));
+    let denoiser = Denoiser {
+        strength: 0.0,
+        ..Default::default()
+    };
+
+    assert_eq!(denoiser.apply(&image, None, None), image);
+}
+
+#[test]
+fn zero_spatial_sigma_keeps_only_the_center_pixel() {
+    let image = Rgb32FImage::from_fn(8, 8, |x, y| Rgb([x as f32, y as f32, 0.5]));
+    let denoiser = Denoiser {
+        sigma_spatial: 0.0,
+        ..Default::default()
+    };
+
+    let filtered = denoiser.apply(&image, None, None);
+    for (p, q) in filtered.pixels().zip(image.pixels()) {
+        assert!(
+            p.0.iter().zip(q.0).all(|(a, b)| (a - b).abs() < 1e-6),
+            "{p:?} vs {q:?}"
+        );
+    }
+}
+
+#[test]
+fn normal_buffer_keeps_edges_sharp() {
+    // Left half faces +Z, right half faces +X, same color everywhere but
+    // with a brightness step at the same edge.
+    let image = Rgb32FImage::from_fn(16, 8, |x, _| {
+        if x < 8 {
+            Rgb([0.2, 0.2, 0.2])
+        } else {
+            Rgb([0.8, 0.8, 0.8])
+        }
+    });
+    let normal = Rgb32FImage::from_fn(16, 8, |x, _| {
+        if x < 8 {
+            Rgb([0.0, 0.0, 1.0])
+        } else {
+            Rgb([1.0, 0.0, 0.0])
+        }
+    });
+    let denoiser = Denoiser {
+        sigma_color: 10.0,
+        ..Default::default()
+    };
+
+    let guided = denoiser.apply(&image, None, Some(&normal));
+    let unguided = denoiser.apply(&image, None, None);
+
+    let step = |img: &Rgb32FImage| img.get_pixel(8, 4).0[0] - img.get_pixel(7, 4).0[0];
+    assert!(step(&guided) > 0.55);
+    assert!(step(&guided) > step(&unguided));
+}
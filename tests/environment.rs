@@ -0,0 +1,250 @@
+mod common;
+
+use std::f64::consts::PI;
+
+use common::{ball_at, mean, rmse};
+use image::{Rgb, Rgb32FImage, Rgba32FImage};
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::{Constant, Cubemap, Environment, EnvironmentMap, Gradient, Transparent},
+    hittable_list::HittableList,
+    material::Lambertian,
+    output::{self, AlphaMode},
+    ray::Point3,
+    sphere::Sphere,
+    tonemap::DisplayTransform,
+    vec3::Vec3,
+};
+
+/// Dim blue sky over a grey ground, with a tiny, very bright sun.
+fn sky_with_sun() -> Rgb32FImage {
+    Rgb32FImage::from_fn(64, 32, |x, y| match (x, y) {
+        (40, 8) => Rgb([20000.0, 18000.0, 15000.0]),
+        (_, 0..=15) => Rgb([0.3, 0.4, 0.6]),
+        _ => Rgb([0.2, 0.2, 0.2]),
+    })
+}
+
+/// Smooth map without a sun, which plain material sampling handles well.
+fn smooth_sky() -> Rgb32FImage {
+    Rgb32FImage::from_fn(64, 32, |x, y| {
+        Rgb([x as f32 / 32.0, 1.0 - y as f32 / 32.0, 0.5])
+    })
+}
+
+fn scene() -> HittableList {
+    let mut world = HittableList::default();
+    world.add(Sphere {
+        center: Point3::new(0.0, -100.5, -1.0),
+        radius: 100.0,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        }),
+    });
+    world.add(Sphere {
+        center: Point3::new(0.0, 0.0, -1.0),
+        radius: 0.5,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.7, 0.3, 0.3),
+        }),
+    });
+    world
+}
+
+fn render(environment: EnvironmentMap, samples_per_pixel: i32, seed: u64) -> Rgba32FImage {
+    CameraBuilder::new()
+        .image_width(32)
+        .samples_per_pixel(samples_per_pixel)
+        .max_depth(6)
+        .seed(seed)
+        .environment(environment)
+        .build()
+        .render(&scene())
+}
+
+fn without_importance_sampling(mut environment: EnvironmentMap) -> EnvironmentMap {
+    environment.importance_sampling = false;
+    environment
+}
+
+#[test]
+fn sampled_directions_integrate_the_map() {
+    let map = EnvironmentMap::new(&sky_with_sun());
+
+    // Radiance integrated over the sphere, texel by texel.
+    let mut exact = 0.0;
+    for (_, y, pixel) in sky_with_sun().enumerate_pixels() {
+        let theta0 = y as f64 / 32.0 * PI;
+        let theta1 = (y + 1) as f64 / 32.0 * PI;
+        let solid_angle = 2.0 * PI / 64.0 * (theta0.cos() - theta1.cos());
+        exact += pixel.0[1] as f64 * solid_angle;
+    }
+
+    let n = 256;
+    let mut estimate = 0.0;
+    for i in 0..n {
+        for j in 0..n {
+            let u = ((i as f64 + 0.5) / n as f64, (j as f64 + 0.5) / n as f64);
+            let (direction, pdf) = map.sample(u).unwrap();
+            assert!((pdf - map.pdf(&direction)).abs() <= 1e-6 * pdf);
+            estimate += map.value(&direction).y / pdf;
+        }
+    }
+    estimate /= (n * n) as f64;
+
+    assert!(
+        (estimate - exact).abs() < 0.01 * exact,
+        "estimated {estimate}, expected {exact}"
+    );
+}
+
+#[test]
+fn rotation_turns_the_map_around_the_vertical_axis() {
+    let map = EnvironmentMap::new(&sky_with_sun());
+    let mut rotated = map.clone();
+    rotated.rotation = 90.0;
+
+    let (sun, _) = map.sample((0.5, 0.5)).unwrap();
+    let turned = Vec3::new(sun.z, sun.y, -sun.x);
+    assert!((rotated.value(&turned) - map.value(&sun)).length() < 1e-9);
+    assert!(map.value(&sun).y > 1000.0);
+}
+
+#[test]
+fn intensity_scales_the_radiance() {
+    let mut map = EnvironmentMap::new(&smooth_sky());
+    let direction = Vec3::new(0.3, 0.2, -1.0);
+    let before = map.value(&direction);
+    map.intensity = 2.5;
+    assert!((map.value(&direction) - before * 2.5).length() < 1e-9);
+}
+
+#[test]
+fn importance_sampling_is_unbiased() {
+    let map = EnvironmentMap::new(&smooth_sky());
+    let sampled = mean(&render(map.clone(), 64, 1));
+    let plain = mean(&render(without_importance_sampling(map), 64, 2));
+
+    assert!(
+        (sampled - plain).abs() < 0.02 * plain,
+        "importance sampled mean {sampled}, plain mean {plain}"
+    );
+}
+
+#[test]
+fn importance_sampling_tames_a_small_sun() {
+    let map = EnvironmentMap::new(&sky_with_sun());
+    let reference = render(map.clone(), 256, 3);
+
+    let sampled = rmse(&render(map.clone(), 8, 4), &reference);
+    let plain = rmse(&render(without_importance_sampling(map), 8, 4), &reference);
+    assert!(
+        sampled < 0.5 * plain,
+        "importance sampled error {sampled} is not clearly below plain error {plain}"
+    );
+}
+
+#[test]
+fn default_gradient_matches_the_original_sky() {
+    let gradient = Gradient::default();
+    let up = gradient.value(&Vec3::new(0.0, 1.0, 0.0));
+    let down = gradient.value(&Vec3::new(0.0, -1.0, 0.0));
+
+    assert!((up - Color::new(0.5, 0.7, 1.0)).length() < 1e-12);
+    assert!((down - Color::new(1.0, 1.0, 1.0)).length() < 1e-12);
+}
+
+#[test]
+fn convex_object_under_constant_light_shows_its_albedo() {
+    let image = CameraBuilder::new()
+        .image_width(16)
+        .samples_per_pixel(4)
+        .environment(Constant {
+            color: Color::new(1.0, 1.0, 1.0),
+        })
+        .build()
+        .render(&ball_at(Point3::new(0.0, 0.0, -1.0)));
+
+    // Every ray bounces off the sphere once and escapes.
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        assert_eq!(a, 1.0);
+        assert!(
+            [r, g, b].iter().all(|&c| (0.499..=1.0).contains(&c)),
+            "{pixel:?}"
+        );
+    }
+    let center = image.get_pixel(8, 4).0;
+    assert!((center[0] - 0.5).abs() < 1e-6, "{center:?}");
+}
+
+#[test]
+fn transparent_background_has_zero_alpha_but_still_lights() {
+    let image = CameraBuilder::new()
+        .image_width(16)
+        .samples_per_pixel(4)
+        .environment(Transparent::new(Constant {
+            color: Color::new(1.0, 1.0, 1.0),
+        }))
+        .build()
+        .render(&ball_at(Point3::new(0.0, 0.0, -1.0)));
+
+    assert_eq!(image.get_pixel(0, 0).0, [0.0; 4]);
+    let center = image.get_pixel(8, 4).0;
+    assert_eq!(center[3], 1.0);
+    assert!((center[0] - 0.5).abs() < 1e-6, "{center:?}");
+
+    // 8-bit output keeps the coverage in its alpha channel.
+    let display = output::to_rgba8(&image, &DisplayTransform::default(), AlphaMode::Auto);
+    assert_eq!(display.get_pixel(0, 0).0[3], 0);
+    assert_eq!(display.get_pixel(8, 4).0[3], 255);
+}
+
+#[test]
+fn cubemap_picks_the_face_along_each_axis() {
+    let face = |v: f32| Rgb32FImage::from_pixel(4, 4, Rgb([v, v, v]));
+    let cubemap = Cubemap::new([
+        face(1.0),
+        face(2.0),
+        face(3.0),
+        face(4.0),
+        face(5.0),
+        face(6.0),
+    ]);
+
+    let axes = [
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(-1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, -1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(0.0, 0.0, -1.0),
+    ];
+    for (i, axis) in axes.iter().enumerate() {
+        assert_eq!(cubemap.value(axis).x, (i + 1) as f64);
+    }
+}
+
+#[test]
+fn cubemap_faces_are_oriented_like_opengl() {
+    // Top half of the -Z face is red, bottom half blue: looking ahead and
+    // slightly up must see red.
+    let mut faces: [Rgb32FImage; 6] = Default::default();
+    for face in &mut faces {
+        *face = Rgb32FImage::new(8, 8);
+    }
+    faces[5] = Rgb32FImage::from_fn(8, 8, |_, y| {
+        if y < 4 {
+            Rgb([1.0, 0.0, 0.0])
+        } else {
+            Rgb([0.0, 0.0, 1.0])
+        }
+    });
+    let cubemap = Cubemap::new(faces);
+
+    let up = cubemap.value(&Vec3::new(0.0, 0.5, -1.0));
+    let down = cubemap.value(&Vec3::new(0.0, -0.5, -1.0));
+    assert!(up.x > 0.99 && up.z < 0.01, "{up:?}");
+    assert!(down.z > 0.99 && down.x < 0.01, "{down:?}");
+}
@@ -0,0 +1,109 @@
+use raytracing_in_one_weekend::{color::Color, film::Film, filter::Filter};
+
+const FILTERS: [Filter; 5] = [
+    Filter::Box { radius: 0.5 },
+    Filter::Tent { radius: 1.0 },
+    Filter::Gaussian {
+        radius: 1.5,
+        sigma: 0.5,
+    },
+    Filter::Mitchell {
+        radius: 2.0,
+        b: 1.0 / 3.0,
+        c: 1.0 / 3.0,
+    },
+    Filter::Lanczos { radius: 3.0 },
+];
+
+/// Deterministic offsets inside a pixel, in [-0.5, 0.5).
+fn offsets(count: usize) -> impl Iterator<Item = (f64, f64)> {
+    let mut state = 0x2545_f491u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f64 / 4_294_967_296.0 - 0.5
+    };
+    (0..count).map(move |_| (next(), next()))
+}
+
+#[test]
+fn half_pixel_box_is_the_per_pixel_average() {
+    let (width, height) = (6, 4);
+    let mut film = Film::new(width, height, Filter::Box { radius: 0.5 });
+    let mut sums = vec![Color::default(); (width * height) as usize];
+
+    let mut offsets = offsets(8 * (width * height) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            for sample in 0..8 {
+                let offset = offsets.next().unwrap();
+                let color = Color::new(x as f64, y as f64, sample as f64 + offset.0);
+                film.add_sample(x, y, offset, color, 1.0);
+                sums[(y * width + x) as usize] += color;
+            }
+        }
+    }
+
+    let image = film.to_image();
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let expected = sums[(y * width + x) as usize] / 8.0;
+        for (c, value) in [expected.x, expected.y, expected.z].into_iter().enumerate() {
+            assert!(
+                (pixel[c] as f64 - value).abs() < 1e-5,
+                "{pixel:?} at ({x}, {y})"
+            );
+        }
+        assert_eq!(pixel[3], 1.0);
+    }
+}
+
+#[test]
+fn every_filter_keeps_a_constant_image_constant() {
+    let color = Color::new(0.25, 0.5, 2.0);
+    for filter in FILTERS {
+        let (width, height) = (8, 8);
+        let mut film = Film::new(width, height, filter);
+        let mut offsets = offsets(16 * (width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                for _ in 0..16 {
+                    film.add_sample(x, y, offsets.next().unwrap(), color, 1.0);
+                }
+            }
+        }
+
+        for pixel in film.to_image().pixels() {
+            let got = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            let expected = [0.25, 0.5, 2.0, 1.0];
+            for (got, expected) in got.into_iter().zip(expected) {
+                assert!((got - expected).abs() < 1e-5, "{pixel:?} with {filter:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn negative_lobes_never_make_negative_or_nan_pixels() {
+    for filter in FILTERS {
+        // A dark pixel next to a very bright one, at the image edge: the
+        // bright sample reaches it through the negative lobe only.
+        let mut film = Film::new(4, 1, filter);
+        film.add_sample(0, 0, (-0.49, 0.0), Color::new(1000.0, 1000.0, 1000.0), 1.0);
+        film.add_sample(2, 0, (0.0, 0.0), Color::default(), 1.0);
+        // A pixel whose weights from two samples cancel out.
+        let mut cancelling = Film::new(4, 1, filter);
+        cancelling.add_sample(0, 0, (-0.5, 0.0), Color::new(1.0, 1.0, 1.0), 1.0);
+        cancelling.add_sample(3, 0, (0.3, 0.0), Color::new(1.0, 1.0, 1.0), 1.0);
+
+        for image in [film.to_image(), cancelling.to_image()] {
+            for pixel in image.pixels() {
+                assert!(
+                    pixel.0.iter().all(|c| c.is_finite() && *c >= 0.0),
+                    "{pixel:?} with {filter:?}"
+                );
+                assert!(pixel[3] <= 1.0, "{pixel:?} with {filter:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Renders small, low-noise versions of the scene presets and compares them
+//! with the images stored in `tests/golden`. A failing comparison writes the
+//! new render and an amplified difference image next to the test binaries
+//! (under `target/tmp/golden`).
+//!
+//! Presets whose own lighting stays noisy at any affordable sample count
+//! are lit differently here; their tests and goldens say so in the name.
+//!
+//! After an intended change in the look of a render, bless the new images
+//! with
+//!
+//! ```text
+//! BLESS=1 cargo test --test golden
+//! ```
+
+use std::{env, fs, path::PathBuf};
+
+use image::{Rgba, RgbaImage};
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    compare::{self, Comparison},
+    environment::{Constant, Gradient},
+    material::Lambertian,
+    output::{self, AlphaMode},
+    projection::Projection,
+    sampler::SamplerKind,
+    scene::{self, Scene},
+};
+
+/// Lowest acceptable peak signal to noise ratio, in dB. The goldens are
+/// rendered with enough samples that a different seed still stays above
+/// 40, while a 10% change in an albedo drops well below.
+const MIN_PSNR: f64 = 38.0;
+/// Lowest acceptable SSIM. It hardly reacts to shading changes, but drops
+/// for lost or moved edges. Sampling noise alone keeps it above 0.98, even
+/// over flat surfaces, where it is most sensitive to noise.
+const MIN_SSIM: f64 = 0.97;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+/// Renders `scene` small, with a low-discrepancy sampler and many samples,
+/// after `configure` has adjusted its camera.
+fn render(
+    scene: Scene,
+    image_width: u32,
+    samples_per_pixel: i32,
+    configure: impl FnOnce(CameraBuilder) -> CameraBuilder,
+) -> RgbaImage {
+    let camera = scene
+        .camera
+        .image_width(image_width)
+        .samples_per_pixel(samples_per_pixel)
+        .sampler(SamplerKind::Sobol)
+        .max_depth(8)
+        .seed(1);
+    let cam = configure(camera).build();
+    output::to_rgba8(&cam.render(&scene.world), &cam.display, AlphaMode::Straight)
+}
+
+/// Compares a render of the preset `scene` with the golden image `name`.
+fn check(
+    name: &str,
+    scene: &str,
+    image_width: u32,
+    samples_per_pixel: i32,
+    configure: impl FnOnce(CameraBuilder) -> CameraBuilder,
+) {
+    let scene = scene::by_name(scene).unwrap();
+    let actual = render(scene, image_width, samples_per_pixel, configure);
+
+    let path = golden_path(name);
+    if env::var_os("BLESS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        actual.save(&path).unwrap();
+        return;
+    }
+    let expected = image::open(&path)
+        .unwrap_or_else(|err| {
+            panic!(
+                "no golden image at {} ({err}); bless one with BLESS=1",
+                path.display()
+            )
+        })
+        .into_rgba8();
+
+    let same_size = expected.dimensions() == actual.dimensions();
+    let comparison = same_size.then(|| compare::compare(&expected, &actual));
+    if let Some(Comparison { psnr, ssim, .. }) = comparison {
+        if psnr >= MIN_PSNR && ssim >= MIN_SSIM {
+            return;
+        }
+    }
+
+    let failures = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    fs::create_dir_all(&failures).unwrap();
+    let actual_path = failures.join(format!("{name}-actual.png"));
+    actual.save(&actual_path).unwrap();
+    if same_size {
+        let diff_path = failures.join(format!("{name}-diff.png"));
+        compare::difference(&expected, &actual, 4.0)
+            .save(&diff_path)
+            .unwrap();
+    }
+    panic!(
+        "{name} differs from {}: {comparison:?}, see {}",
+        path.display(),
+        actual_path.display()
+    );
+}
+
+#[test]
+fn three_spheres() {
+    check("three-spheres", "three-spheres", 24, 256, |camera| camera);
+}
+
+#[test]
+fn random_spheres() {
+    check("random-spheres", "random-spheres", 24, 128, |camera| camera);
+}
+
+#[test]
+fn cornell_box_under_uniform_light() {
+    // Found only by chance, the small ceiling light would keep the render
+    // noisy for thousands of samples. Light the box as brightly from the
+    // open front instead, and expose for it.
+    check(
+        "cornell-box-uniform-light",
+        "cornell-box",
+        16,
+        512,
+        |camera| {
+            camera
+                .environment(Constant {
+                    color: Color::new(15.0, 15.0, 15.0),
+                })
+                .exposure(-15f64.log2())
+        },
+    );
+}
+
+#[test]
+fn material_grid() {
+    check("material-grid", "material-grid", 24, 256, |camera| camera);
+}
+
+#[test]
+fn material_preview_under_gradient_sky() {
+    // Without the sun, whose reflections off the floor are rare fireflies.
+    check(
+        "material-preview-gradient-sky",
+        "material-preview",
+        24,
+        256,
+        |camera| camera.environment(Gradient::default()),
+    );
+}
+
+#[test]
+fn small_shading_change_fails() {
+    // A diffuse ball filling the view, lit as in the material-preview-gradient-sky golden.
+    let ball = |albedo: Color, seed: u64| {
+        let scene = scene::material_preview(Box::new(Lambertian { albedo }));
+        render(scene, 24, 256, |camera| {
+            camera
+                .environment(Gradient::default())
+                .projection(Projection::Perspective { vfov: 15.0 })
+                .seed(seed)
+        })
+    };
+    let albedo = Color::new(0.7, 0.4, 0.3);
+    let expected = ball(albedo, 1);
+
+    let reseeded = compare::compare(&expected, &ball(albedo, 2));
+    assert!(
+        reseeded.psnr >= MIN_PSNR && reseeded.ssim >= MIN_SSIM,
+        "{reseeded:?}"
+    );
+    let darker = compare::compare(&expected, &ball(albedo * 0.9, 1));
+    assert!(darker.psnr < MIN_PSNR, "{darker:?}");
+}
+
+fn noisy(image: &RgbaImage, amount: i32) -> RgbaImage {
+    let mut state = 12345u32;
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let p = image.get_pixel(x, y).0;
+        Rgba(std::array::from_fn(|c| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let offset = (state >> 16) as i32 % (2 * amount + 1) - amount;
+            match c {
+                3 => p[c],
+                _ => (p[c] as i32 + offset).clamp(0, 255) as u8,
+            }
+        }))
+    })
+}
+
+#[test]
+fn metrics_rank_differences() {
+    let image = RgbaImage::from_fn(32, 32, |x, y| {
+        let v = if (x / 4 + y / 4) % 2 == 0 { 40 } else { 200 };
+        Rgba([v, v / 2, 255 - v, 255])
+    });
+
+    let same = compare::compare(&image, &image);
+    assert_eq!(same.rmse, 0.0);
+    assert_eq!(same.psnr, f64::INFINITY);
+    assert!((same.ssim - 1.0).abs() < 1e-12);
+
+    let slight = compare::compare(&image, &noisy(&image, 2));
+    let heavy = compare::compare(&image, &noisy(&image, 40));
+    assert!(
+        slight.psnr > MIN_PSNR && slight.ssim > MIN_SSIM,
+        "{slight:?}"
+    );
+    assert!(
+        heavy.psnr < slight.psnr && heavy.ssim < slight.ssim,
+        "{heavy:?}"
+    );
+    assert!(heavy.psnr < MIN_PSNR, "{heavy:?}");
+
+    // A blur keeps the mean, but loses the checker's edges.
+    let blurred = RgbaImage::from_fn(32, 32, |x, y| {
+        let (x0, x1) = (x.saturating_sub(1), (x + 1).min(31));
+        let average = |c: usize| {
+            (image.get_pixel(x0, y).0[c] as u32
+                + image.get_pixel(x, y).0[c] as u32
+                + image.get_pixel(x1, y).0[c] as u32)
+                / 3
+        };
+        Rgba([average(0) as u8, average(1) as u8, average(2) as u8, 255])
+    });
+    assert!(compare::compare(&image, &blurred).ssim < MIN_SSIM);
+
+    let diff = compare::difference(&image, &noisy(&image, 2), 4.0);
+    assert!(diff.pixels().all(|p| p.0.iter().all(|&c| c <= 8)));
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use image::{GrayImage, Luma, Rgba32FImage};
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::{Constant, Environment},
+    hittable_list::HittableList,
+    lens::{ApertureShape, LensElement, RealisticLens, DOUBLE_GAUSS_50MM},
+    vec3::Vec3,
+};
+
+/// A small, bright light straight ahead, infinitely far away.
+#[derive(Clone, Debug)]
+struct Star;
+
+impl Environment for Star {
+    fn value(&self, direction: &Vec3) -> Color {
+        if direction.unit_vector().z < -0.99999 {
+            Color::new(1.0, 1.0, 1.0)
+        } else {
+            Color::default()
+        }
+    }
+}
+
+/// The star through `lens` on a small film, so its bokeh spans many pixels.
+fn star(lens: RealisticLens) -> Rgba32FImage {
+    CameraBuilder::new()
+        .image_width(48)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(128)
+        .environment(Star)
+        .lens(RealisticLens {
+            film_diagonal: 8.0,
+            ..lens
+        })
+        .build()
+        .render(&HittableList::default())
+}
+
+/// Spread of the star's light around its centroid: the mean of x² and y², in
+/// pixels², and E[x²y²] / (E[x²] E[y²]). The last is 1 for a square
+/// aligned with the axes, 2/3 for a disk and 2/5 for a square on its corner.
+fn spread(image: &Rgba32FImage) -> (f64, f64, f64) {
+    let weighted = |f: &dyn Fn(f64, f64) -> f64| -> f64 {
+        image
+            .enumerate_pixels()
+            .map(|(x, y, p)| p.0[1] as f64 * f(x as f64 + 0.5, y as f64 + 0.5))
+            .sum()
+    };
+    let total = weighted(&|_, _| 1.0);
+    assert!(total > 0.0);
+    let cx = weighted(&|x, _| x) / total;
+    let cy = weighted(&|_, y| y) / total;
+    let xx = weighted(&|x, _| (x - cx).powi(2)) / total;
+    let yy = weighted(&|_, y| (y - cy).powi(2)) / total;
+    let xxyy = weighted(&|x, y| (x - cx).powi(2) * (y - cy).powi(2)) / total;
+    (xx, yy, xxyy / (xx * yy))
+}
+
+fn defocused(aperture_shape: ApertureShape) -> RealisticLens {
+    RealisticLens {
+        focus_distance: 0.5,
+        aperture_shape,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn double_gauss_is_a_50mm_lens() {
+    let focal_length = RealisticLens::default().focal_length().unwrap();
+    assert!((focal_length - 50.0).abs() < 1.0, "{focal_length}");
+}
+
+#[test]
+fn lens_tables_parse() {
+    let table = "# radius thickness ior aperture\n\
+                 29.475\t3.76\t1.67\t25.2\n\
+                 \n\
+                 0 4.5 0 17.1\n";
+    let elements = LensElement::parse_table(table).unwrap();
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[0].radius, DOUBLE_GAUSS_50MM[0].radius);
+    assert_eq!(elements[1].aperture, 17.1);
+
+    assert!(LensElement::parse_table("1 2 3").is_err());
+    assert!(LensElement::parse_table("1 2 x 4").is_err());
+}
+
+#[test]
+fn corners_are_darker_than_the_center() {
+    let image = CameraBuilder::new()
+        .image_width(48)
+        .aspect_ratio(1.5)
+        .samples_per_pixel(64)
+        .environment(Constant {
+            color: Color::new(1.0, 1.0, 1.0),
+        })
+        .lens(RealisticLens::default())
+        .build()
+        .render(&HittableList::default());
+
+    let mean = |x0: u32, y0: u32| {
+        let mut sum = 0.0;
+        for y in y0..y0 + 4 {
+            for x in x0..x0 + 4 {
+                sum += image.get_pixel(x, y).0[1] as f64;
+            }
+        }
+        sum / 16.0
+    };
+    let center = mean(22, 14);
+    let corner = mean(0, 0);
+    assert!((center - 1.0).abs() < 0.05, "center {center}");
+    assert!(corner < 0.7 * center, "corner {corner}, center {center}");
+    // Vignetting darkens, it doesn't make the image transparent.
+    assert_eq!(image.get_pixel(0, 0).0[3], 1.0);
+}
+
+#[test]
+fn focus_decides_how_sharp_a_star_is() {
+    let (sharp, _, _) = spread(&star(RealisticLens {
+        focus_distance: 1e6,
+        ..Default::default()
+    }));
+    let (blurred, _, _) = spread(&star(defocused(ApertureShape::Circle)));
+    assert!(
+        blurred > 10.0 * sharp,
+        "spread {sharp} in focus, {blurred} out of focus"
+    );
+}
+
+#[test]
+fn stopping_down_shrinks_the_bokeh() {
+    let (open, _, _) = spread(&star(defocused(ApertureShape::Circle)));
+    let (stopped, _, _) = spread(&star(RealisticLens {
+        aperture_diameter: Some(17.1 / 2.0),
+        ..defocused(ApertureShape::Circle)
+    }));
+    let ratio = stopped / open;
+    assert!(
+        (ratio - 0.25).abs() < 0.1,
+        "spread {stopped} stopped down, {open} open"
+    );
+}
+
+#[test]
+fn bokeh_takes_the_shape_of_the_aperture() {
+    let circle = spread(&star(defocused(ApertureShape::Circle)));
+    let square = spread(&star(defocused(ApertureShape::Polygon {
+        blades: 4,
+        rotation: 45.0,
+    })));
+    let diamond = spread(&star(defocused(ApertureShape::Polygon {
+        blades: 4,
+        rotation: 0.0,
+    })));
+    let mask = GrayImage::from_fn(16, 16, |x, y| {
+        let inside = (3..13).contains(&x) && (3..13).contains(&y);
+        Luma([if inside { 255 } else { 0 }])
+    });
+    let masked = spread(&star(defocused(ApertureShape::Image(Arc::new(mask)))));
+
+    assert!((circle.2 - 2.0 / 3.0).abs() < 0.1, "circle {circle:?}");
+    assert!(square.2 > 0.9, "square {square:?}");
+    assert!(diamond.2 < 0.55, "diamond {diamond:?}");
+    assert!(masked.2 > 0.9, "square mask {masked:?}");
+}
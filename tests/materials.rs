@@ -0,0 +1,425 @@
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::{self, Color},
+    environment::Constant,
+    hittable::HitRecord,
+    hittable_list::HittableList,
+    material::{
+        Coated, Dielectric, Dispersion, Lambertian, Material, Metal, MixMaterial, Principled,
+        ThinFilm,
+    },
+    ray::{Point3, Ray},
+    sampler::SamplerKind,
+    sphere::Sphere,
+    vec3::Vec3,
+};
+
+/// A ray arriving `angle` degrees off the normal of the plane z = 0, and
+/// its hit at the origin.
+fn incoming(angle: f64) -> (Ray, HitRecord) {
+    let angle = angle.to_radians();
+    let direction = Vec3::new(angle.sin(), 0.0, -angle.cos());
+    let ray = Ray::new(-direction, direction);
+    let mut rec = HitRecord::new();
+    rec.t = 1.0;
+    rec.set_face_normal(&ray, &Vec3::new(0.0, 0.0, 1.0));
+    (ray, rec)
+}
+
+/// `count` independent scatters of `ray` off `mat` at `rec`.
+fn scatter(
+    mat: &dyn Material,
+    ray: &Ray,
+    rec: &HitRecord,
+    count: u32,
+) -> Vec<Option<(Ray, Color)>> {
+    let mut sampler = SamplerKind::Independent.build(7, count);
+    (0..count)
+        .map(|index| {
+            sampler.start_pixel_sample(0, 0, index);
+            sampler.start_bounce(0);
+            mat.scatter(ray, rec, sampler.as_mut())
+        })
+        .collect()
+}
+
+fn close(a: Vec3, b: Vec3, tolerance: f64) -> bool {
+    (a - b).length() < tolerance
+}
+
+#[test]
+fn principled_lobes_never_add_energy() {
+    let white = Color::new(1.0, 1.0, 1.0);
+    for metallic in [0.0, 1.0] {
+        for roughness in [0.05, 0.5, 1.0] {
+            for (clearcoat, sheen) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)] {
+                let mat = Principled {
+                    base_color: white,
+                    metallic,
+                    roughness,
+                    specular: 1.0,
+                    clearcoat,
+                    sheen,
+                    ..Default::default()
+                };
+                for angle in [0.0, 45.0, 85.0] {
+                    let (ray, rec) = incoming(angle);
+                    for (_, attenuation) in scatter(&mat, &ray, &rec, 256).into_iter().flatten() {
+                        assert!(
+                            attenuation.x <= 1.0 && attenuation.y <= 1.0 && attenuation.z <= 1.0,
+                            "{attenuation:?} at {angle}° off {metallic} {roughness}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Mean radiance over the middle of a render of a sphere made of `mat`,
+/// filling the view, inside a uniformly white environment.
+fn furnace(mat: Principled) -> f64 {
+    let mut world = HittableList::default();
+    world.add(Sphere {
+        center: Point3::new(0.0, 0.0, -1.5),
+        radius: 1.0,
+        mat: Box::new(mat),
+    });
+    let image = CameraBuilder::new()
+        .image_width(16)
+        .aspect_ratio(1.0)
+        .samples_per_pixel(64)
+        .max_depth(64)
+        .environment(Constant {
+            color: Color::new(1.0, 1.0, 1.0),
+        })
+        .build()
+        .render(&world);
+
+    let middle: Vec<f64> = image
+        .enumerate_pixels()
+        .filter(|(x, y, _)| (5..11).contains(x) && (5..11).contains(y))
+        .map(|(_, _, p)| color::luminance(Color::new(p[0] as f64, p[1] as f64, p[2] as f64)))
+        .collect();
+    middle.iter().sum::<f64>() / middle.len() as f64
+}
+
+#[test]
+fn white_principled_passes_the_furnace_test() {
+    // A white surface neither absorbs nor adds light, so under a uniform
+    // environment it disappears.
+    let diffuse = Principled {
+        base_color: Color::new(1.0, 1.0, 1.0),
+        specular: 0.0,
+        ..Default::default()
+    };
+    assert!((furnace(diffuse) - 1.0).abs() < 1e-6);
+
+    // Glossy lobes lose the microfacet reflections that end up below the
+    // surface, but never gain anything.
+    for metallic in [0.0, 1.0] {
+        let layered = Principled {
+            base_color: Color::new(1.0, 1.0, 1.0),
+            metallic,
+            specular: 1.0,
+            clearcoat: 1.0,
+            sheen: 1.0,
+            ..Default::default()
+        };
+        let radiance = furnace(layered);
+        assert!((0.9..=1.0 + 1e-6).contains(&radiance), "{radiance}");
+    }
+}
+
+#[test]
+fn fully_metallic_principled_matches_metal() {
+    let base_color = Color::new(0.9, 0.6, 0.2);
+    let principled = Principled::metallic_roughness(base_color, 1.0, 0.0);
+    let metal = Metal {
+        albedo: base_color,
+        fuzz: 0.0,
+    };
+
+    for angle in [0.0, 20.0, 40.0] {
+        let (ray, rec) = incoming(angle);
+        let (expected, albedo) = scatter(&metal, &ray, &rec, 1)[0].take().unwrap();
+        let samples = scatter(&principled, &ray, &rec, 256);
+        for (_, attenuation) in samples.iter().flatten() {
+            // Schlick's Fresnel only brightens the color near grazing angles.
+            assert!(
+                close(*attenuation, albedo, 0.01),
+                "{attenuation:?} at {angle}°"
+            );
+        }
+        // Zero roughness still has a tiny GGX tail, so most (not all)
+        // reflections are the mirror direction.
+        let mirrored = samples
+            .iter()
+            .flatten()
+            .filter(|(scattered, _)| {
+                close(
+                    scattered.direction.unit_vector(),
+                    expected.direction.unit_vector(),
+                    1e-3,
+                )
+            })
+            .count();
+        assert!(mirrored > 230, "{mirrored} mirror reflections at {angle}°");
+    }
+}
+
+#[test]
+fn fully_transmissive_principled_matches_dielectric() {
+    let principled = Principled {
+        base_color: Color::new(1.0, 1.0, 1.0),
+        roughness: 0.0,
+        transmission: 1.0,
+        ior: 1.5,
+        ..Default::default()
+    };
+    let dielectric = Dielectric::new(1.5);
+
+    for angle in [0.0, 45.0, 80.0] {
+        let (ray, rec) = incoming(angle);
+        let principled_samples = scatter(&principled, &ray, &rec, 4096);
+        let dielectric_samples = scatter(&dielectric, &ray, &rec, 4096);
+        assert!(principled_samples.iter().all(Option::is_some));
+
+        let (got, expected) = (
+            reflected_share(&principled_samples),
+            reflected_share(&dielectric_samples),
+        );
+        assert!(
+            (got - expected).abs() < 0.02,
+            "{got} vs {expected} at {angle}°"
+        );
+
+        // Both bend the transmitted ray the same way.
+        let refracted = |samples: &[Option<(Ray, Color)>]| {
+            samples
+                .iter()
+                .flatten()
+                .find(|(scattered, _)| scattered.direction.z < 0.0)
+                .map(|(scattered, attenuation)| (scattered.direction.unit_vector(), *attenuation))
+                .unwrap()
+        };
+        let (got, got_attenuation) = refracted(&principled_samples);
+        let (expected, expected_attenuation) = refracted(&dielectric_samples);
+        assert!(
+            close(got, expected, 1e-3),
+            "{got:?} vs {expected:?} at {angle}°"
+        );
+        assert!(close(got_attenuation, expected_attenuation, 1e-12));
+    }
+}
+
+/// A ray leaving the medium below the plane z = 0 after traveling
+/// `distance` through it, and its hit on the back face.
+fn leaving(distance: f64) -> (Ray, HitRecord) {
+    let direction = Vec3::new(0.0, 0.0, 1.0);
+    let ray = Ray::new(Point3::new(0.0, 0.0, -distance), direction);
+    let mut rec = HitRecord::new();
+    rec.t = distance;
+    rec.set_face_normal(&ray, &Vec3::new(0.0, 0.0, 1.0));
+    (ray, rec)
+}
+
+#[test]
+fn tinted_glass_absorbs_along_the_path() {
+    let color = Color::new(0.5, 0.8, 1.0);
+    let glass = Dielectric::tinted(1.5, color, 2.0);
+
+    for distance in [0.5, 2.0, 6.0] {
+        let (ray, rec) = leaving(distance);
+        let (_, attenuation) = scatter(&glass, &ray, &rec, 1)[0].take().unwrap();
+        // Beer-Lambert: the tint compounds per `distance` traveled.
+        let expected = Color::new(
+            0.5f64.powf(distance / 2.0),
+            0.8f64.powf(distance / 2.0),
+            1.0,
+        );
+        assert!(close(attenuation, expected, 1e-12), "{attenuation:?}");
+    }
+
+    // Entering the glass absorbs nothing yet.
+    let (ray, rec) = incoming(0.0);
+    let (_, attenuation) = scatter(&glass, &ray, &rec, 1)[0].take().unwrap();
+    assert!(close(attenuation, Color::new(1.0, 1.0, 1.0), 1e-12));
+}
+
+#[test]
+fn tint_brighter_than_white_absorbs_nothing() {
+    let glass = Dielectric::tinted(1.5, Color::new(1.5, 1.0, 0.5), 1.0);
+    let (ray, rec) = leaving(2.0);
+    let (_, attenuation) = scatter(&glass, &ray, &rec, 1)[0].take().unwrap();
+    assert!(
+        close(attenuation, Color::new(1.0, 1.0, 0.25), 1e-12),
+        "{attenuation:?}"
+    );
+}
+
+#[test]
+#[should_panic(expected = "tint distance must be positive")]
+fn tint_over_no_distance_is_rejected() {
+    Dielectric::tinted(1.5, Color::new(0.5, 0.5, 0.5), 0.0);
+}
+
+#[test]
+fn dispersive_glass_refracts_each_wavelength_by_its_own_index() {
+    let glass = Dielectric::dispersive(Dispersion::BK7);
+    let angle = 45f64.to_radians();
+
+    let mut refracted_angles = Vec::new();
+    for wavelength in [None, Some(450.0), Some(650.0)] {
+        let (mut ray, rec) = incoming(45.0);
+        ray.wavelength = wavelength;
+        let samples = scatter(&glass, &ray, &rec, 64);
+        let (refracted, _) = samples
+            .iter()
+            .flatten()
+            .find(|(scattered, _)| scattered.direction.z < 0.0)
+            .unwrap();
+        let sin_refracted = refracted.direction.unit_vector().x;
+
+        // Snell's law at the index for the ray's wavelength, or at the d
+        // line for RGB rays.
+        let index = Dispersion::BK7
+            .index_at(wavelength.unwrap_or(Dispersion::D_LINE))
+            .unwrap();
+        assert!((sin_refracted - angle.sin() / index).abs() < 1e-9);
+        refracted_angles.push(sin_refracted.asin());
+    }
+
+    // Blue bends more than red.
+    assert!(refracted_angles[1] < refracted_angles[2]);
+}
+
+/// Share of `samples` that reflected back above the plane z = 0.
+fn reflected_share(samples: &[Option<(Ray, Color)>]) -> f64 {
+    let up = samples
+        .iter()
+        .flatten()
+        .filter(|(scattered, _)| scattered.direction.z > 0.0)
+        .count();
+    up as f64 / samples.len() as f64
+}
+
+#[test]
+fn thin_film_over_glass_reflects_off_the_glass_once() {
+    // A film with the glass's own index is just more glass, so the film's
+    // reflectance is the whole reflectance of the glass.
+    let bare = Dielectric::new(1.5);
+    let filmed = ThinFilm {
+        base: Box::new(bare),
+        thickness: 300.0,
+        film_ior: 1.5,
+        base_ior: 1.5,
+    };
+
+    for angle in [0.0, 30.0] {
+        let (mut ray, rec) = incoming(angle);
+        ray.wavelength = Some(550.0);
+        let got = scatter(&filmed, &ray, &rec, 8192);
+        let expected = reflected_share(&scatter(&bare, &ray, &rec, 8192));
+        assert!(
+            (reflected_share(&got) - expected).abs() < 0.015,
+            "{} vs {expected} at {angle}°",
+            reflected_share(&got)
+        );
+        // A single wavelength has a grey reflectance, which the reweighting
+        // turns into white.
+        for (_, attenuation) in got.iter().flatten() {
+            assert!(close(*attenuation, Color::new(1.0, 1.0, 1.0), 1e-6));
+        }
+    }
+}
+
+#[test]
+fn thin_film_reflections_are_iridescent() {
+    let film = ThinFilm {
+        base: Box::new(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        }),
+        thickness: 400.0,
+        film_ior: 1.4,
+        base_ior: 1.5,
+    };
+    let (ray, rec) = incoming(0.0);
+    let samples = scatter(&film, &ray, &rec, 1024);
+
+    let mirrored = samples
+        .iter()
+        .flatten()
+        .find(|(scattered, _)| close(scattered.direction, Vec3::new(0.0, 0.0, 1.0), 1e-9))
+        .map(|(_, attenuation)| *attenuation)
+        .unwrap();
+    let spread =
+        mirrored.x.max(mirrored.y).max(mirrored.z) - mirrored.x.min(mirrored.y).min(mirrored.z);
+    assert!(spread > 0.1, "{mirrored:?}");
+
+    // On average the film only splits the light, never adds to it.
+    let mean = samples
+        .iter()
+        .flatten()
+        .fold(Color::default(), |sum, (_, a)| sum + *a)
+        / samples.len() as f64;
+    assert!(mean.x <= 1.0 && mean.y <= 1.0 && mean.z <= 1.0, "{mean:?}");
+}
+
+#[test]
+fn mix_picks_each_material_by_its_weight() {
+    let red = || -> Box<dyn Material> {
+        Box::new(Lambertian {
+            albedo: Color::new(1.0, 0.0, 0.0),
+        })
+    };
+    let blue = || -> Box<dyn Material> {
+        Box::new(Lambertian {
+            albedo: Color::new(0.0, 0.0, 1.0),
+        })
+    };
+    let (ray, rec) = incoming(0.0);
+
+    for weight in [0.0, 0.25, 1.0] {
+        let mix = MixMaterial::new(red(), blue(), weight);
+        let samples = scatter(&mix, &ray, &rec, 4096);
+        let blue_share = samples
+            .iter()
+            .flatten()
+            .filter(|(_, attenuation)| attenuation.z > 0.5)
+            .count() as f64
+            / samples.len() as f64;
+        assert!(
+            (blue_share - weight).abs() < 0.02,
+            "{blue_share} at {weight}"
+        );
+        assert!(close(
+            mix.albedo(&rec),
+            Color::new(1.0 - weight, 0.0, weight),
+            1e-12
+        ));
+    }
+}
+
+#[test]
+fn coating_reflects_with_its_fresnel_share() {
+    // Over a black base, only the coating's reflections carry any light.
+    let coated = Coated::new(Box::new(Lambertian::default()));
+    let r0 = 0.04;
+
+    for angle in [0.0, 60.0, 80.0] {
+        let (ray, rec) = incoming(angle);
+        let samples = scatter(&coated, &ray, &rec, 4096);
+        let coat_share = samples
+            .iter()
+            .filter(|sample| !matches!(sample, Some((_, attenuation)) if attenuation.x == 0.0))
+            .count() as f64
+            / samples.len() as f64;
+        let cosine = angle.to_radians().cos();
+        let expected = r0 + (1.0 - r0) * (1.0 - cosine).powi(5);
+        assert!(
+            (coat_share - expected).abs() < 0.015,
+            "{coat_share} vs {expected} at {angle}°"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::{env, fs};
+
+use image::{Rgba, Rgba32FImage};
+use raytracing_in_one_weekend::output::{self, AlphaMode};
+
+/// A small opaque HDR image with a different value in every channel,
+/// including radiance well above 1.
+fn gradient() -> Rgba32FImage {
+    Rgba32FImage::from_fn(3, 2, |x, y| {
+        let base = (y * 3 + x) as f32;
+        Rgba([base * 0.5, base * 2.0 + 0.25, 100.0 / (base + 1.0), 1.0])
+    })
+}
+
+#[test]
+fn pfm_is_little_endian_and_bottom_up() {
+    let image = gradient();
+    let path = env::temp_dir().join("output_test.pfm");
+    output::save_linear(&image, AlphaMode::Auto, &path).unwrap();
+    let bytes = fs::read(&path).unwrap();
+    let _ = fs::remove_file(&path);
+
+    let header = b"PF\n3 2\n-1.0\n";
+    assert_eq!(&bytes[..header.len()], header);
+    let values: Vec<f32> = bytes[header.len()..]
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    assert_eq!(values.len(), 3 * 2 * 3);
+
+    // The bottom row comes first.
+    let expected: Vec<f32> = [1, 0]
+        .into_iter()
+        .flat_map(|y| (0..3).map(move |x| (x, y)))
+        .flat_map(|(x, y)| image.get_pixel(x, y).0[..3].to_vec())
+        .collect();
+    assert_eq!(values, expected);
+}
+
+#[test]
+fn exr_keeps_every_value() {
+    let image = gradient();
+    let path = env::temp_dir().join("output_test.exr");
+    output::save_linear(&image, AlphaMode::Auto, &path).unwrap();
+    let read = image::open(&path).unwrap().into_rgba32f();
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(read, image);
+}
+
+#[test]
+fn hdr_keeps_values_to_rgbe_precision() {
+    let image = gradient();
+    let path = env::temp_dir().join("output_test.hdr");
+    output::save_linear(&image, AlphaMode::Auto, &path).unwrap();
+    let read = image::open(&path).unwrap().into_rgb32f();
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(read.dimensions(), image.dimensions());
+    for (x, y, pixel) in read.enumerate_pixels() {
+        let expected = image.get_pixel(x, y).0;
+        // RGBE shares one exponent, so each channel is exact to within 1%
+        // of the pixel's largest channel.
+        let largest = expected[..3].iter().copied().fold(0.0, f32::max);
+        for c in 0..3 {
+            assert!(
+                (pixel[c] - expected[c]).abs() <= 0.01 * largest,
+                "{pixel:?} vs {expected:?} at ({x}, {y})"
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use std::ops::ControlFlow;
+
+use common::{camera, scene};
+
+#[test]
+fn passes_add_up_to_a_single_shot_render() {
+    let world = scene();
+    let single = camera(12).build().render(&world);
+
+    let mut passes = Vec::new();
+    let (progressive, _) =
+        camera(12)
+            .samples_per_pass(5)
+            .build()
+            .render_progressive(&world, |pass| {
+                passes.push((pass.index, pass.samples_per_pixel));
+                ControlFlow::Continue(())
+            });
+
+    assert_eq!(passes, [(1, 5), (2, 10), (3, 12)]);
+    assert_eq!(progressive, single);
+}
+
+#[test]
+fn stopping_early_returns_the_image_so_far() {
+    let world = scene();
+    let (stopped, _) = camera(64)
+        .samples_per_pass(4)
+        .build()
+        .render_progressive(&world, |pass| {
+            if pass.index == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+    assert_eq!(stopped, camera(8).build().render(&world));
+}
+
+#[test]
+fn snapshot_is_written_after_each_pass() {
+    let path = std::env::temp_dir().join("progressive_snapshot_test.png");
+    let _ = std::fs::remove_file(&path);
+
+    camera(4)
+        .samples_per_pass(2)
+        .snapshot(&path)
+        .build()
+        .render_progressive(&scene(), |_| {
+            assert!(path.exists());
+            ControlFlow::Continue(())
+        });
+
+    let snapshot = image::open(&path).unwrap();
+    assert_eq!(snapshot.width(), 32);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use std::f64::consts::PI;
+
+use common::{ball_at, transparent_camera};
+use image::{Rgb, Rgb32FImage, Rgba32FImage};
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    color::Color,
+    environment::{Constant, EnvironmentMap},
+    hittable_list::HittableList,
+    projection::Projection,
+    ray::Point3,
+    vec3::Vec3,
+};
+
+fn close(a: Vec3, b: Vec3) -> bool {
+    (a - b).length() < 1e-9
+}
+
+fn direction(projection: Projection, st: (f64, f64), aspect_ratio: f64) -> Vec3 {
+    projection.ray(st, aspect_ratio).unwrap().1
+}
+
+fn coverage(image: &Rgba32FImage) -> f64 {
+    let sum: f64 = image.pixels().map(|p| p.0[3] as f64).sum();
+    sum / (image.width() * image.height()) as f64
+}
+
+#[test]
+fn default_is_the_original_pinhole() {
+    let projection = Projection::default();
+    let aspect_ratio = 16.0 / 9.0;
+
+    assert!(close(
+        direction(projection, (0.5, 0.5), aspect_ratio),
+        Vec3::new(0.0, 0.0, -1.0)
+    ));
+    assert!(close(
+        direction(projection, (0.0, 0.0), aspect_ratio),
+        Vec3::new(-aspect_ratio, 1.0, -1.0)
+    ));
+}
+
+#[test]
+fn orthographic_rays_are_parallel() {
+    let projection = Projection::Orthographic { height: 4.0 };
+    for st in [(0.0, 0.0), (0.3, 0.8), (1.0, 1.0)] {
+        let (origin, direction) = projection.ray(st, 2.0).unwrap();
+        assert!(close(direction, Vec3::new(0.0, 0.0, -1.0)));
+        assert_eq!(origin.z, 0.0);
+    }
+    let (corner, _) = projection.ray((0.0, 0.0), 2.0).unwrap();
+    assert!(close(corner, Vec3::new(-4.0, 2.0, 0.0)));
+}
+
+#[test]
+fn isometric_view_of_a_ball_is_a_centered_disk() {
+    let image = transparent_camera(32, 16)
+        .center(Point3::new(3.0, 3.0, 3.0))
+        .look_at(Point3::new(0.0, 0.0, 0.0))
+        .projection(Projection::Orthographic { height: 2.0 })
+        .build()
+        .render(&ball_at(Point3::new(0.0, 0.0, 0.0)));
+
+    // A disk of radius 0.5 in a 2x2 view.
+    let expected = PI * 0.25 / 4.0;
+    let got = coverage(&image);
+    assert!(
+        (got - expected).abs() < 0.01 * expected,
+        "{got} vs {expected}"
+    );
+    assert_eq!(image.get_pixel(16, 16).0[3], 1.0);
+    assert_eq!(image.get_pixel(0, 0).0[3], 0.0);
+}
+
+#[test]
+fn look_at_turns_the_camera() {
+    let image = transparent_camera(32, 16)
+        .look_at(Point3::new(1.0, 0.0, 0.0))
+        .up(Vec3::new(0.0, 0.0, 1.0))
+        .build()
+        .render(&ball_at(Point3::new(2.0, 0.0, 0.0)));
+
+    assert_eq!(image.get_pixel(16, 16).0[3], 1.0);
+    assert_eq!(image.get_pixel(0, 16).0[3], 0.0);
+}
+
+#[test]
+fn camera_looks_down_minus_z_from_wherever_it_is() {
+    let image = transparent_camera(32, 16)
+        .center(Point3::new(0.0, 0.0, -5.0))
+        .build()
+        .render(&ball_at(Point3::new(0.0, 0.0, -7.0)));
+
+    assert_eq!(image.get_pixel(16, 16).0[3], 1.0);
+}
+
+#[test]
+fn looking_along_up_still_renders() {
+    let image = transparent_camera(32, 16)
+        .center(Point3::new(0.0, 3.0, 0.0))
+        .look_at(Point3::new(0.0, 0.0, 0.0))
+        .build()
+        .render(&ball_at(Point3::new(0.0, 0.0, 0.0)));
+
+    assert!(image.pixels().flat_map(|p| p.0).all(f32::is_finite));
+    assert_eq!(image.get_pixel(16, 16).0[3], 1.0);
+}
+
+#[test]
+#[should_panic(expected = "coincides with its center")]
+fn looking_at_the_center_is_rejected() {
+    transparent_camera(32, 16)
+        .center(Point3::new(0.0, 0.0, -1.0))
+        .look_at(Point3::new(0.0, 0.0, -1.0))
+        .build();
+}
+
+#[test]
+fn fisheyes_map_the_image_circle_to_the_field_of_view() {
+    let equidistant = Projection::EquidistantFisheye { fov: 180.0 };
+    let equisolid = Projection::EquisolidFisheye { fov: 180.0 };
+
+    for projection in [equidistant, equisolid] {
+        assert!(close(
+            direction(projection, (0.5, 0.5), 1.0),
+            Vec3::new(0.0, 0.0, -1.0)
+        ));
+        // The edge of the circle is 90° off axis.
+        assert!(close(
+            direction(projection, (1.0, 0.5), 1.0),
+            Vec3::new(1.0, 0.0, 0.0)
+        ));
+        assert!(close(
+            direction(projection, (0.5, 0.0), 1.0),
+            Vec3::new(0.0, 1.0, 0.0)
+        ));
+        assert!(projection.ray((0.0, 0.0), 1.0).is_none());
+    }
+
+    // Halfway out: 45° for equidistant, 2 asin(sin(45°) / 2) for equisolid.
+    let angle = |projection| {
+        direction(projection, (0.75, 0.5), 1.0)
+            .z
+            .abs()
+            .acos()
+            .to_degrees()
+    };
+    assert!((angle(equidistant) - 45.0).abs() < 1e-9);
+    let expected = 2.0 * (0.5 * 45f64.to_radians().sin()).asin().to_degrees();
+    assert!((angle(equisolid) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn fisheye_corners_are_transparent() {
+    let image = transparent_camera(32, 16)
+        .environment(Constant {
+            color: Color::new(1.0, 1.0, 1.0),
+        })
+        .projection(Projection::EquisolidFisheye { fov: 180.0 })
+        .build()
+        .render(&HittableList::default());
+
+    assert_eq!(image.get_pixel(0, 0).0, [0.0; 4]);
+    assert_eq!(image.get_pixel(16, 16).0, [1.0; 4]);
+}
+
+#[test]
+fn cylindrical_wraps_around_and_stays_flat_vertically() {
+    let projection = Projection::Cylindrical { vfov: 90.0 };
+
+    assert!(close(
+        direction(projection, (0.75, 0.5), 4.0),
+        Vec3::new(1.0, 0.0, 0.0)
+    ));
+    assert!(close(
+        direction(projection, (0.0, 0.5), 4.0),
+        Vec3::new(0.0, 0.0, 1.0)
+    ));
+    assert!(close(
+        direction(projection, (0.5, 0.0), 4.0),
+        Vec3::new(0.0, 1.0, -1.0)
+    ));
+    assert!(close(
+        direction(projection, (0.25, 1.0), 4.0),
+        Vec3::new(-1.0, -1.0, 0.0)
+    ));
+}
+
+#[test]
+fn equirectangular_render_reproduces_an_environment_map() {
+    let map = Rgb32FImage::from_fn(64, 32, |x, y| Rgb([x as f32 / 64.0, y as f32 / 32.0, 0.5]));
+    let image = CameraBuilder::new()
+        .image_width(64)
+        .aspect_ratio(2.0)
+        .samples_per_pixel(4)
+        .projection(Projection::Equirectangular)
+        .environment(EnvironmentMap::new(&map))
+        .build()
+        .render(&HittableList::default());
+
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let expected = map.get_pixel(x, y).0;
+        for c in 0..3 {
+            assert!(
+                (pixel.0[c] - expected[c]).abs() < 1e-3,
+                "({x}, {y}): {pixel:?} vs {expected:?}"
+            );
+        }
+    }
+}
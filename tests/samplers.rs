@@ -0,0 +1,105 @@
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder, color::Color, hittable_list::HittableList, material::Lambertian,
+    ray::Point3, sampler::SamplerKind, sphere::Sphere,
+};
+
+const KINDS: [SamplerKind; 4] = [
+    SamplerKind::Independent,
+    SamplerKind::Stratified,
+    SamplerKind::Halton,
+    SamplerKind::Sobol,
+];
+
+/// Mean squared error of estimating the area of a quarter disk with
+/// `samples` points per pixel, over many pixels. `bounce` picks which
+/// dimensions are used.
+fn quarter_disk_error(kind: SamplerKind, samples: u32, bounce: Option<u32>) -> f64 {
+    let mut sampler = kind.build(7, samples);
+    let exact = std::f64::consts::FRAC_PI_4;
+    let pixels = 32;
+
+    let mut error = 0.0;
+    for y in 0..pixels {
+        for x in 0..pixels {
+            let mut inside = 0;
+            for index in 0..samples {
+                sampler.start_pixel_sample(x, y, index);
+                if let Some(bounce) = bounce {
+                    sampler.start_bounce(bounce);
+                }
+                let (u, v) = sampler.get_2d();
+                if u * u + v * v < 1.0 {
+                    inside += 1;
+                }
+            }
+            let estimate = inside as f64 / samples as f64;
+            error += (estimate - exact).powi(2);
+        }
+    }
+    error / (pixels * pixels) as f64
+}
+
+#[test]
+fn samples_are_in_the_unit_interval() {
+    for kind in KINDS {
+        let mut sampler = kind.build(1, 16);
+        for index in 0..64 {
+            sampler.start_pixel_sample(3, 5, index);
+            for bounce in 0..4 {
+                sampler.start_bounce(bounce);
+                for _ in 0..20 {
+                    let u = sampler.get_1d();
+                    assert!((0.0..1.0).contains(&u), "{kind:?} produced {u}");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn low_discrepancy_samplers_converge_faster_than_independent() {
+    for bounce in [None, Some(0), Some(3)] {
+        let independent = quarter_disk_error(SamplerKind::Independent, 16, bounce);
+        // Halton's bases at deep bounces are too large to help at 16 samples.
+        let kinds: &[SamplerKind] = match bounce {
+            Some(3) => &[SamplerKind::Stratified, SamplerKind::Sobol],
+            _ => &[
+                SamplerKind::Stratified,
+                SamplerKind::Halton,
+                SamplerKind::Sobol,
+            ],
+        };
+        for &kind in kinds {
+            let error = quarter_disk_error(kind, 16, bounce);
+            assert!(
+                error < 0.8 * independent,
+                "{kind:?} at bounce {bounce:?}: {error} vs independent {independent}"
+            );
+        }
+    }
+}
+
+#[test]
+fn renders_are_deterministic_for_a_seed() {
+    let mut world = HittableList::default();
+    world.add(Sphere {
+        center: Point3::new(0.0, 0.0, -1.0),
+        radius: 0.5,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        }),
+    });
+
+    for kind in KINDS {
+        let camera = |seed| {
+            CameraBuilder::new()
+                .image_width(16)
+                .samples_per_pixel(4)
+                .sampler(kind)
+                .seed(seed)
+                .build()
+        };
+        assert_eq!(camera(3).render(&world), camera(3).render(&world));
+        assert_ne!(camera(3).render(&world), camera(4).render(&world));
+    }
+}
@@ -0,0 +1,122 @@
+use raytracing_in_one_weekend::{
+    color::Color,
+    hittable::Hittable,
+    interval::Interval,
+    material::{DiffuseLight, Lambertian, Material},
+    quad::{cuboid, Quad},
+    ray::{Point3, Ray},
+    scene,
+    vec3::Vec3,
+};
+
+fn any_hit() -> Interval {
+    Interval::new(0.001, f64::INFINITY)
+}
+
+#[test]
+fn every_preset_is_found_by_name() {
+    for name in scene::NAMES {
+        assert!(scene::by_name(name).is_some(), "{name}");
+    }
+    assert!(scene::by_name("teapot").is_none());
+}
+
+#[test]
+fn quads_are_hit_only_inside_their_sides() {
+    let quad = Quad::new(
+        Point3::new(-1.0, -1.0, -2.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        Box::new(Lambertian::default()),
+    );
+    let toward = |x: f64, y: f64| Ray::new(Point3::default(), Vec3::new(x, y, -1.0));
+
+    let hit = quad.hit(&toward(0.25, 0.5), &any_hit()).unwrap();
+    assert!((hit.t - 2.0).abs() < 1e-12);
+    assert!((hit.u - 0.75).abs() < 1e-12 && (hit.v - 0.5).abs() < 1e-12);
+    assert!(hit.front_face);
+    assert!(quad.hit(&toward(0.6, 0.0), &any_hit()).is_none());
+    assert!(quad
+        .hit(
+            &Ray::new(Point3::default(), Vec3::new(1.0, 0.0, 0.0)),
+            &any_hit()
+        )
+        .is_none());
+}
+
+#[test]
+fn cuboid_faces_point_outwards() {
+    let cube = cuboid(
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(-1.0, -1.0, -1.0),
+        Box::new(Lambertian::default()),
+    );
+    for axis in [
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+    ] {
+        for side in [-1.0, 1.0] {
+            let ray = Ray::new(axis * (3.0 * side), axis * -side);
+            let hit = cube.hit(&ray, &any_hit()).unwrap();
+            assert!((hit.t - 2.0).abs() < 1e-12);
+            assert!(hit.front_face, "{axis:?} {side}");
+            assert!((hit.normal - axis * side).length() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn lights_shine_from_their_front_face() {
+    let light = Quad::new(
+        Point3::new(-1.0, -1.0, -1.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Box::new(DiffuseLight {
+            emit: Color::new(4.0, 4.0, 4.0),
+        }),
+    );
+    let front = Ray::new(Point3::default(), Vec3::new(0.0, 0.0, -1.0));
+    let back = Ray::new(Point3::new(0.0, 0.0, -2.0), Vec3::new(0.0, 0.0, 1.0));
+
+    let hit = light.hit(&front, &any_hit()).unwrap();
+    assert_eq!(hit.mat.emitted(&hit).x, 4.0);
+    let hit = light.hit(&back, &any_hit()).unwrap();
+    assert_eq!(hit.mat.emitted(&hit).x, 0.0);
+    assert!(Lambertian::default().emitted(&hit).near_zero());
+}
+
+#[test]
+fn cornell_box_is_lit_only_by_its_light() {
+    let scene = scene::cornell_box();
+    let image = scene
+        .camera
+        .image_width(32)
+        .samples_per_pixel(16)
+        .max_depth(8)
+        .build()
+        .render(&scene.world);
+
+    let brightest = image.pixels().map(|p| p.0[1]).fold(0.0, f32::max);
+    assert!(brightest > 14.0, "light seen at {brightest}");
+    // Every pixel sees the inside of the box, which the light brightens.
+    assert!(image.pixels().all(|p| p.0[3] == 1.0));
+    let mean = image.pixels().map(|p| p.0[1] as f64).sum::<f64>() / (32.0 * 32.0);
+    assert!(mean > 0.05 && mean < 1.0, "mean {mean}");
+}
+
+#[test]
+fn random_spheres_follow_their_seed() {
+    let render = |seed: u64| {
+        let scene = scene::random_spheres(seed);
+        scene
+            .camera
+            .image_width(48)
+            .samples_per_pixel(2)
+            .max_depth(4)
+            .build()
+            .render(&scene.world)
+    };
+    assert_eq!(render(3), render(3));
+    assert_ne!(render(3), render(4));
+}
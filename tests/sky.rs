@@ -0,0 +1,103 @@
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder, color::Color, environment::Environment, hittable_list::HittableList,
+    material::Lambertian, ray::Point3, sky::SunSky, sphere::Sphere, vec3::Vec3,
+};
+
+#[test]
+fn clear_sky_is_blue_overhead() {
+    let sky = SunSky::default();
+    let zenith = sky.value(&Vec3::new(0.0, 1.0, 0.0));
+
+    assert!(zenith.z > zenith.x, "zenith {zenith:?} is not blue");
+    assert!(zenith.y > 0.05 && zenith.y < 5.0, "zenith {zenith:?}");
+}
+
+#[test]
+fn low_sun_is_redder_and_dimmer() {
+    let noon = SunSky {
+        elevation: 80.0,
+        ..Default::default()
+    }
+    .sun_radiance();
+    let sunset = SunSky {
+        elevation: 3.0,
+        ..Default::default()
+    }
+    .sun_radiance();
+
+    assert!(sunset.x / sunset.z > 2.0 * noon.x / noon.z);
+    assert!(sunset.y < noon.y);
+}
+
+#[test]
+fn sampled_directions_land_on_the_sun() {
+    let sky = SunSky {
+        elevation: 30.0,
+        azimuth: 60.0,
+        ..Default::default()
+    };
+    let sun = sky.sun_direction();
+
+    for i in 0..16 {
+        for j in 0..16 {
+            let u = ((i as f64 + 0.5) / 16.0, (j as f64 + 0.5) / 16.0);
+            let (direction, pdf) = sky.sample(u).unwrap();
+            assert!(direction.dot(&sun) > sky.sun_radius.to_radians().cos() - 1e-9);
+            assert_eq!(pdf, sky.pdf(&direction));
+            assert!(sky.value(&direction).y > 1000.0);
+        }
+    }
+    assert_eq!(sky.pdf(&Vec3::new(0.0, 1.0, 0.0)), 0.0);
+}
+
+#[test]
+fn resizing_the_sun_keeps_its_power() {
+    let power = |sky: &SunSky| sky.sun_radiance().y / sky.pdf(&sky.sun_direction());
+    let small = SunSky::default();
+    let large = SunSky {
+        sun_radius: 5.0,
+        ..Default::default()
+    };
+
+    let (small, large) = (power(&small), power(&large));
+    assert!((small - large).abs() < 0.01 * small, "{small} vs {large}");
+}
+
+#[test]
+fn sunlit_ground_is_brighter_than_shadowed_ground() {
+    let mut world = HittableList::default();
+    world.add(Sphere {
+        center: Point3::new(0.0, -100.5, -1.0),
+        radius: 100.0,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        }),
+    });
+    world.add(Sphere {
+        center: Point3::new(0.0, 0.0, -1.0),
+        radius: 0.5,
+        mat: Box::new(Lambertian {
+            albedo: Color::new(0.5, 0.5, 0.5),
+        }),
+    });
+
+    // Sun behind the camera on the right, so the sphere's shadow falls
+    // to the left of it.
+    let image = CameraBuilder::new()
+        .image_width(64)
+        .aspect_ratio(2.0)
+        .samples_per_pixel(32)
+        .max_depth(4)
+        .environment(SunSky {
+            elevation: 30.0,
+            azimuth: 135.0,
+            ..Default::default()
+        })
+        .build()
+        .render(&world);
+
+    let lit = image.get_pixel(8, 28).0[1];
+    let shadowed = image.get_pixel(24, 24).0[1];
+    assert!(lit.is_finite() && shadowed.is_finite());
+    assert!(lit > 2.0 * shadowed, "lit {lit}, shadowed {shadowed}");
+}
@@ -0,0 +1,60 @@
+use raytracing_in_one_weekend::{color::Color, sampler::SamplerKind, spectrum};
+
+/// `spectral_to_rgb` of the upsampled `rgb`, averaged over `count`
+/// wavelengths drawn by `wavelength(i)` from [0, 1).
+fn round_trip(rgb: Color, count: u32, mut wavelength: impl FnMut(u32) -> f64) -> Color {
+    let mut sum = Color::default();
+    for i in 0..count {
+        let lambda = spectrum::sample_wavelength(wavelength(i));
+        sum += spectrum::spectral_to_rgb(spectrum::rgb_to_spectrum(rgb, lambda), lambda);
+    }
+    sum / count as f64
+}
+
+fn evenly_spread(rgb: Color) -> Color {
+    let count = 4000;
+    round_trip(rgb, count, |i| (i as f64 + 0.5) / count as f64)
+}
+
+fn max_difference(a: Color, b: Color) -> f64 {
+    [a.x - b.x, a.y - b.y, a.z - b.z]
+        .into_iter()
+        .map(f64::abs)
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn greys_survive_the_round_trip() {
+    for value in [1.0, 0.5, 0.1] {
+        let grey = Color::new(value, value, value);
+        assert!(max_difference(evenly_spread(grey), grey) < 1e-5);
+    }
+}
+
+#[test]
+fn primaries_come_back_within_a_few_percent() {
+    for primary in [
+        Color::new(1.0, 0.0, 0.0),
+        Color::new(0.0, 1.0, 0.0),
+        Color::new(0.0, 0.0, 1.0),
+        Color::new(0.8, 0.3, 0.1),
+    ] {
+        let got = evenly_spread(primary);
+        assert!(
+            max_difference(got, primary) < 0.05,
+            "{primary:?} came back as {got:?}"
+        );
+    }
+}
+
+#[test]
+fn randomly_sampled_wavelengths_converge_to_the_same_color() {
+    let mut sampler = SamplerKind::Independent.build(3, 1);
+    for rgb in [Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 1.0)] {
+        let got = round_trip(rgb, 20_000, |i| {
+            sampler.start_pixel_sample(0, 0, i);
+            sampler.get_1d()
+        });
+        assert!(max_difference(got, evenly_spread(rgb)) < 0.03, "{got:?}");
+    }
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::ball_at;
+use image::Rgba32FImage;
+use raytracing_in_one_weekend::{
+    camera::CameraBuilder,
+    hittable_list::HittableList,
+    projection::{Projection, Stereo, StereoLayout},
+    ray::Point3,
+    vec3::Vec3,
+};
+
+fn camera(stereo: Stereo) -> CameraBuilder {
+    common::transparent_camera(64, 8).stereo(stereo)
+}
+
+/// Alpha weighted mean column of the pixels in `columns` x `rows`, relative
+/// to the first column.
+fn centroid(image: &Rgba32FImage, columns: (u32, u32), rows: (u32, u32)) -> f64 {
+    let (mut sum, mut total) = (0.0, 0.0);
+    for y in rows.0..rows.1 {
+        for x in columns.0..columns.1 {
+            let alpha = image.get_pixel(x, y).0[3] as f64;
+            sum += alpha * (x - columns.0) as f64;
+            total += alpha;
+        }
+    }
+    assert!(total > 0.0);
+    sum / total
+}
+
+#[test]
+fn layouts_double_the_image() {
+    let world = HittableList::default();
+    let side_by_side = camera(Stereo::default()).build().render(&world);
+    let over_under = camera(Stereo {
+        layout: StereoLayout::OverUnder,
+        ..Default::default()
+    })
+    .build()
+    .render(&world);
+
+    assert_eq!(side_by_side.dimensions(), (128, 64));
+    assert_eq!(over_under.dimensions(), (64, 128));
+}
+
+#[test]
+fn parallel_eyes_see_near_objects_shifted_inwards() {
+    let world = ball_at(Point3::new(0.0, 0.0, -2.0));
+    let image = camera(Stereo {
+        eye_separation: 0.5,
+        ..Default::default()
+    })
+    .build()
+    .render(&world);
+
+    let left = centroid(&image, (0, 64), (0, 64));
+    let right = centroid(&image, (64, 128), (0, 64));
+    assert!(left > right + 5.0, "left {left}, right {right}");
+}
+
+#[test]
+fn objects_at_the_convergence_distance_line_up() {
+    let world = ball_at(Point3::new(0.0, 0.0, -2.0));
+    let image = camera(Stereo {
+        eye_separation: 0.5,
+        convergence: 2.0,
+        layout: StereoLayout::OverUnder,
+    })
+    .build()
+    .render(&world);
+
+    // About ten pixels apart with parallel eyes.
+    let left = centroid(&image, (0, 64), (0, 64));
+    let right = centroid(&image, (0, 64), (64, 128));
+    assert!((left - right).abs() < 0.5, "left {left}, right {right}");
+}
+
+#[test]
+fn ods_eyes_sit_beside_every_view_direction() {
+    let stereo = Stereo {
+        eye_separation: 0.064,
+        ..Default::default()
+    };
+    let projection = Projection::Equirectangular;
+    for st in [(0.5, 0.5), (0.75, 0.3), (0.1, 0.7), (0.0, 0.5)] {
+        let mono = projection.ray(st, 2.0).unwrap();
+        let (left, left_direction) = stereo.eye_ray(&projection, -1.0, mono);
+        let (right, right_direction) = stereo.eye_ray(&projection, 1.0, mono);
+
+        assert!((left + right).length() < 1e-12);
+        assert!((right.length() - 0.032).abs() < 1e-12);
+        assert!(right.dot(&mono.1).abs() < 1e-12);
+        assert!((left_direction - mono.1).length() < 1e-12);
+        assert!((right_direction - mono.1).length() < 1e-12);
+        // The right eye is on the right when looking along the ray.
+        assert!(right.cross(&mono.1).y > 0.0);
+    }
+
+    let straight_up = projection.ray((0.3, 0.0), 2.0).unwrap();
+    let (eye, _) = stereo.eye_ray(&projection, 1.0, straight_up);
+    assert!(eye.length() < 1e-6);
+}
+
+#[test]
+fn ods_panorama_has_parallax_all_around() {
+    let stereo = Stereo {
+        eye_separation: 0.5,
+        layout: StereoLayout::OverUnder,
+        ..Default::default()
+    };
+    for (center, columns) in [
+        (Point3::new(2.0, 0.0, 0.0), (32, 64)),
+        (Point3::new(-2.0, 0.0, 0.0), (0, 32)),
+        (Point3::new(0.0, 0.0, 2.0), (48, 64)),
+    ] {
+        let image = camera(stereo)
+            .aspect_ratio(2.0)
+            .projection(Projection::Equirectangular)
+            .build()
+            .render(&ball_at(center));
+
+        let left = centroid(&image, columns, (0, 32));
+        let right = centroid(&image, columns, (32, 64));
+        assert!(left > right + 1.0, "{center:?}: left {left}, right {right}");
+    }
+}
+
+#[test]
+fn stereo_works_with_any_projection() {
+    let world = ball_at(Point3::new(0.0, 0.0, -2.0));
+    let fisheye = camera(Stereo::default())
+        .projection(Projection::EquidistantFisheye { fov: 180.0 })
+        .build()
+        .render(&world);
+    assert_eq!(fisheye.get_pixel(32, 32).0[3], 1.0);
+    assert_eq!(fisheye.get_pixel(96, 32).0[3], 1.0);
+    assert_eq!(fisheye.get_pixel(64, 0).0[3], 0.0);
+}
+
+#[test]
+fn flat_projections_offset_the_eyes_along_the_right_axis() {
+    let stereo = Stereo::default();
+    assert!(stereo.convergence.is_infinite());
+    let mono = (Vec3::default(), Vec3::new(0.0, 0.0, -1.0));
+    let (eye, direction) = stereo.eye_ray(&Projection::default(), 1.0, mono);
+    assert!((eye - Vec3::new(0.032, 0.0, 0.0)).length() < 1e-12);
+    assert!((direction - mono.1).length() < 1e-12);
+}
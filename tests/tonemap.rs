@@ -0,0 +1,98 @@
+use raytracing_in_one_weekend::{
+    color::Color,
+    tonemap::{DisplayTransform, ToneMapper, TransferFunction},
+};
+
+fn grey(value: f64) -> Color {
+    Color::new(value, value, value)
+}
+
+/// `mapper` applied to a grey of `value`, checked to stay grey (up to the
+/// rounding in the published ACES and AgX matrices).
+fn map(mapper: ToneMapper, value: f64) -> f64 {
+    let mapped = mapper.apply(grey(value));
+    assert!(
+        (mapped.x - mapped.y).abs() < 1e-3 && (mapped.y - mapped.z).abs() < 1e-3,
+        "{mapped:?} from {value} with {mapper:?}"
+    );
+    mapped.y
+}
+
+#[test]
+fn reinhard_halves_one_and_approaches_white() {
+    assert_eq!(map(ToneMapper::Reinhard, 0.0), 0.0);
+    assert_eq!(map(ToneMapper::Reinhard, 1.0), 0.5);
+    let bright = map(ToneMapper::Reinhard, 1e6);
+    assert!(bright < 1.0 && bright > 0.999_99, "{bright}");
+}
+
+#[test]
+fn extended_reinhard_maps_its_white_point_to_one() {
+    let mapper = ToneMapper::ReinhardExtended { white: 4.0 };
+    assert_eq!(map(mapper, 0.0), 0.0);
+    assert!((map(mapper, 1.0) - 0.531_25).abs() < 1e-12);
+    assert!((map(mapper, 4.0) - 1.0).abs() < 1e-12);
+    assert_eq!(map(mapper, 1e6), 1.0);
+}
+
+#[test]
+fn filmic_curves_cover_black_to_white() {
+    for mapper in [ToneMapper::AcesFilmic, ToneMapper::AgX] {
+        assert!(map(mapper, 0.0) < 1e-3, "{mapper:?}");
+        let mid = map(mapper, 1.0);
+        assert!((0.2..0.9).contains(&mid), "{mid} with {mapper:?}");
+        let bright = map(mapper, 1e3);
+        assert!((0.99..=1.0).contains(&bright), "{bright} with {mapper:?}");
+    }
+}
+
+#[test]
+fn every_curve_rises_and_stays_in_range() {
+    for mapper in [
+        ToneMapper::Clamp,
+        ToneMapper::Reinhard,
+        ToneMapper::ReinhardExtended { white: 4.0 },
+        ToneMapper::AcesFilmic,
+        ToneMapper::AgX,
+    ] {
+        let mut previous = map(mapper, 0.0);
+        for step in 1..=200 {
+            let value = map(mapper, 0.05 * step as f64);
+            assert!(value >= previous - 1e-12, "{mapper:?} falls at step {step}");
+            assert!((0.0..=1.0).contains(&value), "{value} with {mapper:?}");
+            previous = value;
+        }
+        // Negative radiance is treated as black.
+        assert_eq!(map(mapper, -1.0), map(mapper, 0.0));
+    }
+}
+
+fn srgb(value: f64) -> f64 {
+    TransferFunction::Srgb.encode(grey(value)).x
+}
+
+#[test]
+fn srgb_curve_is_linear_near_black_and_keeps_its_endpoints() {
+    assert_eq!(srgb(0.0), 0.0);
+    assert!((srgb(1.0) - 1.0).abs() < 1e-12);
+    for value in [0.0001, 0.001, 0.003] {
+        assert!((srgb(value) - 12.92 * value).abs() < 1e-15, "{value}");
+    }
+    // The two pieces meet at the threshold, up to the standard's rounding.
+    let threshold = 0.003_130_8;
+    assert!((srgb(threshold) - srgb(threshold + 1e-12)).abs() < 1e-6);
+    assert!((srgb(0.5) - 0.735_357).abs() < 1e-6);
+}
+
+#[test]
+fn display_transform_defaults_to_srgb() {
+    let display = DisplayTransform::default();
+    assert!((display.apply(grey(0.5)).x - srgb(0.5)).abs() < 1e-12);
+
+    // One stop of exposure doubles the radiance before encoding.
+    let brighter = DisplayTransform {
+        exposure: 1.0,
+        ..Default::default()
+    };
+    assert!((brighter.apply(grey(0.25)).x - srgb(0.5)).abs() < 1e-12);
+}